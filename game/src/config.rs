@@ -0,0 +1,229 @@
+//! Launch options, read from `snake.cfg` in the working directory and then
+//! overridden by `--key value` pairs on the command line.
+//!
+//! `rules` names a rule preset or a rule file and brings that rule set's
+//! arena along, so arena keys meant to change it have to come after it. The
+//! arena from the settings only counts until then.
+//! `level` names a level file with walls to place inside the arena.
+use crate::controls;
+use crate::settings::{Settings, SharedSettings};
+use snake::arena::{Arena, EdgeRule};
+use snake::level::Level;
+use snake::rules::{Preset, RuleSet};
+use std::str::FromStr;
+
+pub const CONFIG_FILE: &str = "snake.cfg";
+
+/// Who the snakes in a new game are steered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Solo,
+    /// Local players sharing the keyboard.
+    Versus,
+    /// One player against a computer snake.
+    Challenge,
+    /// Computer snakes only.
+    Watch,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 4] = [Mode::Solo, Mode::Versus, Mode::Challenge, Mode::Watch];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Solo => "Solo",
+            Mode::Versus => "Versus",
+            Mode::Challenge => "Versus AI",
+            Mode::Watch => "Watch",
+        }
+    }
+
+    /// Short name used in files, such as the high score table.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Mode::Solo => "solo",
+            Mode::Versus => "versus",
+            Mode::Challenge => "challenge",
+            Mode::Watch => "watch",
+        }
+    }
+
+    /// The launch config with its players and controllers set for this mode.
+    /// Versus keeps whatever players the config file asked for.
+    pub fn configure(&self, config: &Config) -> Config {
+        let mut config = config.clone();
+        config.mode = *self;
+        match self {
+            Mode::Solo => {
+                config.players = 1;
+                config.controllers.clear();
+            }
+            Mode::Versus => config.players = config.players.max(2),
+            Mode::Challenge => {
+                config.players = 2;
+                config.controllers = vec![None, Some("survivor".to_string())];
+            }
+            Mode::Watch => {
+                config.players = 2;
+                config.controllers = vec![Some("seeker".to_string()), Some("survivor".to_string())];
+            }
+        }
+        config
+    }
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Solo
+    }
+}
+
+impl FromStr for Mode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Mode::ALL
+            .iter()
+            .find(|mode| mode.key() == s)
+            .copied()
+            .ok_or(())
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    pub seed: Option<u64>,
+    /// Replay file to play back on launch.
+    pub replay: Option<String>,
+    /// What the game plays by, arena included unless `arena` is set.
+    pub rules: RuleSet,
+    /// Arena picked in the settings or on the command line over the rules'.
+    pub arena: Option<Arena>,
+    /// Walls inside the arena.
+    pub level: Level,
+    /// Local players sharing the keyboard, at least one.
+    pub players: usize,
+    /// AI controller name for each player slot, `None` for a human.
+    pub controllers: Vec<Option<String>>,
+    pub mode: Mode,
+    /// Name high scores are saved under.
+    pub name: String,
+    pub settings: SharedSettings,
+}
+
+impl Config {
+    /// The arena the game plays on.
+    pub fn arena(&self) -> Arena {
+        self.arena.unwrap_or(self.rules.arena)
+    }
+
+    /// Starts from the saved settings, which the config file and command
+    /// line can override for this run.
+    pub fn load() -> Self {
+        let settings = Settings::load();
+        let mut config = Config {
+            arena: settings.arena,
+            settings: settings.shared(),
+            ..Default::default()
+        };
+        if let Ok(contents) = std::fs::read_to_string(CONFIG_FILE) {
+            config.apply_file(&contents);
+        }
+        config.apply_args(std::env::args().skip(1));
+        if config.mode == Mode::Solo && config.players > 1 {
+            config.mode = Mode::Versus;
+        }
+        if config.name.is_empty() {
+            config.name = "Player".to_string();
+        }
+        config
+    }
+
+    fn apply_file(&mut self, contents: &str) {
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                self.set(key.trim(), value.trim());
+            }
+        }
+    }
+
+    fn apply_args(&mut self, mut args: impl Iterator<Item = String>) {
+        while let Some(arg) = args.next() {
+            let key = match arg.strip_prefix("--") {
+                Some(key) => key.to_string(),
+                _ => continue,
+            };
+            if let Some(value) = args.next() {
+                self.set(&key, &value);
+            }
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "seed" => self.seed = u64::from_str(value).ok(),
+            "replay" => self.replay = Some(value.to_string()),
+            "name" => self.name = value.to_string(),
+            "rules" => {
+                let rules = match Preset::from_str(value) {
+                    Ok(preset) => preset.rules(),
+                    _ => match RuleSet::load(value) {
+                        Ok(rules) => rules,
+                        Err(err) => {
+                            eprintln!("Failed to load rules from {}: {:?}", value, err);
+                            return;
+                        }
+                    },
+                };
+                self.rules = rules;
+                self.arena = None;
+            }
+            "level" => match Level::load(value) {
+                Ok(level) => self.level = level,
+                Err(err) => eprintln!("Failed to load level from {}: {:?}", value, err),
+            },
+            "mode" => {
+                if let Ok(mode) = Mode::from_str(value) {
+                    self.mode = mode;
+                }
+            }
+            "players" => {
+                if let Ok(players) = usize::from_str(value) {
+                    self.players = players.max(1).min(controls::MAX_PLAYERS);
+                }
+            }
+            "player1" | "player2" | "player3" | "player4" => {
+                let slot = usize::from_str(&key[6..]).expect("Matched a digit") - 1;
+                if self.controllers.len() <= slot {
+                    self.controllers.resize(slot + 1, None);
+                }
+                self.controllers[slot] = match value {
+                    "human" => None,
+                    name => Some(name.to_string()),
+                };
+            }
+            "arena_width" => {
+                if let Ok(width) = i32::from_str(value) {
+                    let arena = self.arena();
+                    self.arena = Some(Arena::new(width, arena.height, arena.edge));
+                }
+            }
+            "arena_height" => {
+                if let Ok(height) = i32::from_str(value) {
+                    let arena = self.arena();
+                    self.arena = Some(Arena::new(arena.width, height, arena.edge));
+                }
+            }
+            "edges" => {
+                if let Ok(edge) = EdgeRule::from_str(value) {
+                    let arena = self.arena();
+                    self.arena = Some(Arena::new(arena.width, arena.height, edge));
+                }
+            }
+            _ => {}
+        }
+    }
+}
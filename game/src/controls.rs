@@ -0,0 +1,205 @@
+use miniquad::KeyCode;
+
+use crate::components::{Direction, Player};
+pub use snake::input::Action;
+
+pub const MAX_PLAYERS: usize = 4;
+
+/// Up, Right, Down and Left keys for each local player, in player order.
+const LAYOUTS: [[KeyCode; 4]; MAX_PLAYERS] = [
+    [KeyCode::W, KeyCode::D, KeyCode::S, KeyCode::A],
+    [KeyCode::Up, KeyCode::Right, KeyCode::Down, KeyCode::Left],
+    [KeyCode::I, KeyCode::L, KeyCode::K, KeyCode::J],
+    [KeyCode::Kp8, KeyCode::Kp6, KeyCode::Kp5, KeyCode::Kp4],
+];
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+];
+
+/// Keys that can be bound, by the name they have in the settings file.
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::Kp0, "Kp0"),
+    (KeyCode::Kp1, "Kp1"),
+    (KeyCode::Kp2, "Kp2"),
+    (KeyCode::Kp3, "Kp3"),
+    (KeyCode::Kp4, "Kp4"),
+    (KeyCode::Kp5, "Kp5"),
+    (KeyCode::Kp6, "Kp6"),
+    (KeyCode::Kp7, "Kp7"),
+    (KeyCode::Kp8, "Kp8"),
+    (KeyCode::Kp9, "Kp9"),
+    (KeyCode::KpEnter, "KpEnter"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Escape, "Escape"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::LeftShift, "LeftShift"),
+    (KeyCode::RightShift, "RightShift"),
+    (KeyCode::LeftControl, "LeftControl"),
+    (KeyCode::RightControl, "RightControl"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Semicolon, "Semicolon"),
+];
+
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(code, _)| *code == key)
+        .map(|(_, name)| *name)
+}
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, key)| key.eq_ignore_ascii_case(name))
+        .map(|(code, _)| *code)
+}
+
+/// Every bindable action, in the order the settings menu lists them.
+pub fn actions() -> Vec<Action> {
+    let mut actions: Vec<Action> = (0..MAX_PLAYERS)
+        .flat_map(|layout| {
+            DIRECTIONS
+                .iter()
+                .map(move |direction| Action::Turn(Player(layout), *direction))
+        })
+        .collect();
+    actions.extend_from_slice(&[Action::Pause, Action::Back, Action::Confirm, Action::Boost]);
+    actions
+}
+
+pub fn label(action: Action) -> String {
+    match action {
+        Action::Turn(layout, direction) => format!("P{} {}", layout.0 + 1, direction),
+        Action::Pause => "Pause".to_string(),
+        Action::Back => "Back".to_string(),
+        Action::Confirm => "Confirm".to_string(),
+        Action::Boost => "Boost".to_string(),
+    }
+}
+
+/// Which action each key triggers. A key does one thing, an action can have
+/// several keys.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bindings {
+    keys: Vec<(KeyCode, Action)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let mut keys = Vec::new();
+        for (layout, layout_keys) in LAYOUTS.iter().enumerate() {
+            for (key, direction) in layout_keys.iter().zip(DIRECTIONS.iter()) {
+                keys.push((*key, Action::Turn(Player(layout), *direction)));
+            }
+        }
+        keys.push((KeyCode::Escape, Action::Back));
+        keys.push((KeyCode::P, Action::Pause));
+        keys.push((KeyCode::Enter, Action::Confirm));
+        keys.push((KeyCode::Space, Action::Confirm));
+        keys.push((KeyCode::LeftShift, Action::Boost));
+        Self { keys }
+    }
+}
+
+impl Bindings {
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        self.keys
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, action)| *action)
+    }
+
+    pub fn keys_for(&self, action: Action) -> impl Iterator<Item = KeyCode> + '_ {
+        self.keys
+            .iter()
+            .filter(move |(_, bound)| *bound == action)
+            .map(|(key, _)| *key)
+    }
+
+    /// Name of the first key bound to `action`, for prompts.
+    pub fn key_name(&self, action: Action) -> &'static str {
+        self.keys_for(action).find_map(key_name).unwrap_or("?")
+    }
+
+    /// Replaces the keys for `action` with `keys`, taking them off whatever
+    /// they were bound to before. Refuses, returning false, when that would
+    /// leave any action without a key.
+    pub fn set(&mut self, action: Action, keys: &[KeyCode]) -> bool {
+        let orphaned = self.keys.iter().any(|(key, bound)| {
+            *bound != action
+                && keys.contains(key)
+                && self.keys_for(*bound).all(|other| keys.contains(&other))
+        });
+        if keys.is_empty() || orphaned {
+            return false;
+        }
+        self.replace(action, keys);
+        true
+    }
+
+    /// `set` without the checks. Reading saved bindings one action at a time
+    /// can leave another action keyless until its own line comes up.
+    pub fn replace(&mut self, action: Action, keys: &[KeyCode]) {
+        self.keys
+            .retain(|(bound_key, bound)| *bound != action && !keys.contains(bound_key));
+        self.keys.extend(keys.iter().map(|key| (*key, action)));
+    }
+}
+
+/// The player a layout steers in a game of `players`. With a single player
+/// every layout steers the same snake.
+pub fn seat(layout: Player, players: usize) -> Option<Player> {
+    match players {
+        0 | 1 => Some(Player(0)),
+        _ if layout.0 < players => Some(layout),
+        _ => None,
+    }
+}
@@ -1,5 +1,6 @@
 pub mod font;
 pub mod renderer;
+pub mod systems;
 pub mod ui;
 pub use renderer::MainRenderer;
 
@@ -32,7 +32,8 @@ pub enum RenderAssetCommands {
         text: String,
         font: String,
     },
-    // TOOD(jhurstwright): I really want to blindly create, and GC old texts later
+    /// Swaps one use of `text` for `new_text`. The buffers for `text` are only
+    /// freed once nothing else loaded the same string.
     UpdateText {
         new_text: String,
         text: String,
@@ -174,6 +175,14 @@ impl MaterialAsset {
     }
 }
 
+/// GPU buffers for one string, shared by every text showing that string.
+pub struct TextAsset {
+    pub vertices: Vec<miniquad::Buffer>,
+    pub indices: miniquad::Buffer,
+    /// Loads of the string that haven't been updated away yet.
+    users: usize,
+}
+
 pub struct MainRenderer {
     pub ctx: miniquad::Context,
     pub debug_font_bindings: miniquad::Bindings,
@@ -182,7 +191,7 @@ pub struct MainRenderer {
     pub render_font_commands: Vec<RenderFontCommand>,
     pub asset_commands: Vec<RenderAssetCommands>,
     pub fonts: HashMap<String, font::Font>,
-    pub texts: HashMap<String, (Vec<miniquad::Buffer>, miniquad::Buffer)>,
+    pub texts: HashMap<String, TextAsset>,
     pub meshes: Meshes,
     pub materials: Materials,
     pub projection: glam::Mat4,
@@ -403,31 +412,48 @@ impl MainRenderer {
     pub fn load_assets(&mut self) {
         let commands: Vec<RenderAssetCommands> = self.asset_commands.drain(..).collect();
         commands.iter().for_each(|cmd| match cmd {
-            RenderAssetCommands::LoadText { text, font } => {
-                if !self.texts.contains_key(text) {
-                    let buffer = create_text_buffer(self, text.clone(), font);
-                    if let Some(buffers) = buffer {
-                        self.texts.insert(text.clone(), buffers);
-                    }
-                }
-            }
+            RenderAssetCommands::LoadText { text, font } => self.load_text(text, font),
             RenderAssetCommands::UpdateText {
                 text,
                 font,
                 new_text,
             } => {
-                if let Some((vertices, indices)) = self.texts.remove(text) {
-                    vertices.iter().for_each(|b| b.delete());
-                    indices.delete();
-                }
-                let buffer = create_text_buffer(self, new_text.clone(), font);
-                if let Some(buffers) = buffer {
-                    self.texts.insert(new_text.clone(), buffers);
-                }
+                self.load_text(new_text, font);
+                self.release_text(text);
             }
         });
     }
 
+    /// Adds a user to the buffers for `text`, creating them on first use.
+    fn load_text(&mut self, text: &str, font: &String) {
+        if let Some(asset) = self.texts.get_mut(text) {
+            asset.users += 1;
+            return;
+        }
+        if let Some((vertices, indices)) = create_text_buffer(self, text.to_string(), font) {
+            let asset = TextAsset {
+                vertices,
+                indices,
+                users: 1,
+            };
+            self.texts.insert(text.to_string(), asset);
+        }
+    }
+
+    /// Drops a user of the buffers for `text`, freeing them with the last one.
+    fn release_text(&mut self, text: &str) {
+        let asset = match self.texts.get_mut(text) {
+            Some(asset) => asset,
+            _ => return,
+        };
+        asset.users -= 1;
+        if asset.users == 0 {
+            let asset = self.texts.remove(text).expect("Text was just found");
+            asset.vertices.iter().for_each(|b| b.delete());
+            asset.indices.delete();
+        }
+    }
+
     fn draw_main_target(&mut self) {
         let mut uniform = crate::shaders::sprite::VertexUniforms {
             projection: self.projection,
@@ -465,7 +491,11 @@ impl MainRenderer {
             .iter()
             .filter_map(|cmd| cmd.into_draw_font())
         {
-            let (v, i) = &self
+            let TextAsset {
+                vertices: v,
+                indices: i,
+                ..
+            } = &self
                 .texts
                 .get(&font_cmd.text)
                 .expect("Text should be in GPU memory, but isn't");
@@ -486,7 +516,12 @@ impl MainRenderer {
         // Render the Font
         for cmd in self.render_font_commands.iter() {
             let RenderFontCommand { text, position, .. } = cmd;
-            if let Some((v, i)) = &self.texts.get(text) {
+            if let Some(TextAsset {
+                vertices: v,
+                indices: i,
+                ..
+            }) = &self.texts.get(text)
+            {
                 let model = glam::Mat4::from_rotation_translation(
                     glam::Quat::from_axis_angle(glam::Vec3::new(0., 0., 1.), (0.0f32).to_radians()),
                     glam::Vec3::new(position.x, position.y, 0.),
@@ -0,0 +1,127 @@
+use glam::Vec2;
+
+use crate::components;
+use crate::graphics;
+use crate::graphics::renderer;
+use crate::systems::GameWorld;
+use snake::schedule::{Phase, Schedule};
+
+/// Gathers draw commands for part of the world.
+pub type RenderSystem = fn(&mut GameWorld, &mut graphics::MainRenderer);
+
+/// Render systems every game draws with. Heading arrows are added on top
+/// when the settings ask for them.
+pub fn render_schedule() -> Schedule<RenderSystem> {
+    let mut schedule: Schedule<RenderSystem> = Schedule::new();
+    schedule.add("gather", Phase::RenderGather, gather_render_cmds);
+    schedule
+        .add("text", Phase::RenderGather, draw_text)
+        .after("gather");
+    schedule
+}
+
+/// Adds or removes the heading arrows, drawn between the world and the HUD.
+pub fn show_heading_arrows(schedule: &mut Schedule<RenderSystem>, show: bool) {
+    if show {
+        schedule
+            .add("heading_arrows", Phase::RenderGather, debug_render_cmds)
+            .after("gather")
+            .before("text");
+    } else {
+        schedule.remove("heading_arrows");
+    }
+}
+
+pub fn create_text(str: &str) -> (renderer::RenderAssetCommands, components::Text) {
+    let text = components::Text::new(str);
+    let cmd = load_text_command(&text);
+    (cmd, text)
+}
+
+pub fn load_text_command(text: &components::Text) -> renderer::RenderAssetCommands {
+    renderer::RenderAssetCommands::LoadText {
+        text: text.text(),
+        font: text.font(),
+    }
+}
+
+pub fn sync_text_system(game_world: &mut GameWorld, cmds: &mut Vec<renderer::RenderAssetCommands>) {
+    let GameWorld { world, .. } = game_world;
+    for (_, text) in &mut world.query::<&mut components::Text>() {
+        if let Some(stale) = text.take_stale() {
+            cmds.push(renderer::RenderAssetCommands::UpdateText {
+                text: stale,
+                font: text.font(),
+                new_text: text.text(),
+            });
+        }
+    }
+}
+
+/// Darkens everything drawn before it, for overlays shown over the game.
+pub fn dim_command() -> renderer::RenderCommand {
+    renderer::RenderCommand::DrawMesh2D(renderer::DrawMesh2D {
+        rotation: 0f32,
+        material: "Dim".into(),
+        mesh: "Dim".into(),
+        position: Vec2::new(0., 0.),
+    })
+}
+
+pub fn gather_render_cmds(game_world: &mut GameWorld, renderer: &mut graphics::MainRenderer) {
+    let GameWorld { world, .. } = game_world;
+    let main_draw_commands = &mut renderer.main_render_target.commands;
+    for (_, (mesh, material, pos)) in &mut world.query::<(
+        &components::Mesh,
+        &components::Material,
+        &components::Position,
+    )>() {
+        main_draw_commands.push(renderer::RenderCommand::DrawMesh2D(renderer::DrawMesh2D {
+            rotation: 0f32,
+            material: material.0.clone(),
+            mesh: mesh.0.clone(),
+            position: pos.0,
+        }));
+    }
+    for (_, body) in &mut world.query::<&components::Body>() {
+        for (x, y) in body.segments() {
+            main_draw_commands.push(renderer::RenderCommand::DrawMesh2D(renderer::DrawMesh2D {
+                rotation: 0f32,
+                material: body.material.0.clone(),
+                mesh: body.mesh.0.clone(),
+                position: Vec2::new(x as f32, y as f32),
+            }));
+        }
+    }
+}
+
+pub fn debug_render_cmds(game_world: &mut GameWorld, renderer: &mut graphics::MainRenderer) {
+    let GameWorld { world, .. } = game_world;
+
+    let debug_draw_commands = &mut renderer.debug_render_target.commands;
+    for (_, (dir, pos)) in &mut world.query::<(&components::HeadDirection, &components::Position)>()
+    {
+        let vel = dir.0.velocity();
+        let velocity = Vec2::new(vel.x, vel.y * -1.);
+        let angle = velocity.angle_between(Vec2::new(1., 0.));
+        debug_draw_commands.push(renderer::RenderCommand::DrawMesh2D(renderer::DrawMesh2D {
+            material: "Arrow".into(),
+            mesh: "Arrow".into(),
+            position: vel + pos.0,
+            rotation: angle,
+        }));
+    }
+}
+
+pub fn draw_text(game_world: &mut GameWorld, renderer: &mut graphics::MainRenderer) {
+    let GameWorld { world, .. } = game_world;
+
+    let main_draw_commands = &mut renderer.main_render_target.commands;
+    for (_, (text, pos)) in &mut world.query::<(&components::Text, &components::Position)>() {
+        main_draw_commands.push(renderer::RenderCommand::DrawFont(renderer::DrawFont {
+            text: text.text().clone(),
+            font: "KenneyFuture".into(),
+            position: pos.0,
+        }));
+    }
+}
@@ -1,23 +1,30 @@
 use graphics::ui;
 use miniquad::*;
-use stages::GameState;
+use snake::gesture::{Gesture, GestureRecognizer};
+use snake::{components, systems, types};
+use stages::Stage;
 use std::time::Instant;
 
-mod components;
-mod events;
+mod config;
+mod controls;
 mod graphics;
+mod settings;
 mod shaders;
 mod stages;
-mod systems;
-mod types;
+mod storage;
+mod timer;
 mod utils;
 
 pub use types::{AssetIdentity, Color};
 
 struct SnakeGame {
     input: components::Input,
+    gestures: GestureRecognizer,
+    /// The touch being followed, further fingers are ignored.
+    touch: Option<u64>,
     painter: ui::MegaUI,
     renderer: graphics::MainRenderer,
+    settings: settings::SharedSettings,
     stages: stages::StageStack,
     start_time: Instant,
 }
@@ -28,19 +35,49 @@ impl SnakeGame {
         let painter = ui::MegaUI::new(&mut ctx);
         let mut renderer = graphics::MainRenderer::new(ctx);
 
+        let config = config::Config::load();
         let mut input = components::Input::default();
         let mut stages = stages::new_stage_stack(16);
-        let init_state = GameState::new(&input, &mut renderer);
-        let game_stage = Box::new(init_state);
-
-        stages.push(game_stage as Box<dyn stages::Stage>);
+        let replay = config.replay.clone();
+        let settings = config.settings.clone();
+        let thresholds = settings.borrow().gestures;
+        let gestures = GestureRecognizer::new(Box::new(timer::DateTime), thresholds);
+        let mut title = stages::Title::new(&input, config);
+        title.enter(&mut renderer);
+
+        stages.push(Box::new(title) as Box<dyn stages::Stage>);
+
+        if let Some(path) = &replay {
+            match snake::replay::Replay::load(path) {
+                Ok(replay) => {
+                    if let Some(title) = stages.last_mut() {
+                        title.exit(&mut renderer);
+                    }
+                    let mut replay_stage = stages::ReplayState::new(&input, &mut renderer, replay);
+                    replay_stage.enter(&mut renderer);
+                    stages.push(Box::new(replay_stage));
+                }
+                Err(err) => eprintln!("Failed to load replay {}: {:?}", path, err),
+            }
+        }
 
         SnakeGame {
             painter,
             start_time: Instant::now(),
             stages,
             renderer,
+            settings,
             input,
+            gestures,
+            touch: None,
+        }
+    }
+
+    fn gesture(&mut self, gesture: Option<Gesture>) {
+        match gesture {
+            Some(Gesture::Swipe(direction)) => self.input.press(direction),
+            Some(Gesture::Tap(position)) => self.input.tap = Some(position),
+            _ => {}
         }
     }
 }
@@ -53,13 +90,47 @@ impl EventHandlerFree for SnakeGame {
         self.input.resized = true;
     }
 
-    fn mouse_button_up_event(&mut self, _button: MouseButton, _x: f32, _y: f32) {
+    fn mouse_button_up_event(&mut self, button: MouseButton, x: f32, y: f32) {
+        if button == MouseButton::Left {
+            let gesture = self.gestures.release(glam::Vec2::new(x, y));
+            self.gesture(gesture);
+        }
     }
 
-    fn mouse_button_down_event(&mut self, _button: MouseButton, _x: f32, _y: f32) {
+    fn mouse_button_down_event(&mut self, button: MouseButton, x: f32, y: f32) {
+        if button == MouseButton::Left {
+            self.gestures.press(glam::Vec2::new(x, y));
+        }
     }
 
     fn mouse_motion_event(&mut self, x: f32, y: f32) {
+        let gesture = self.gestures.moved(glam::Vec2::new(x, y));
+        self.gesture(gesture);
+    }
+
+    fn touch_event(&mut self, phase: TouchPhase, id: u64, x: f32, y: f32) {
+        let position = glam::Vec2::new(x, y);
+        match phase {
+            TouchPhase::Started if self.touch.is_none() => {
+                self.touch = Some(id);
+                self.gestures.press(position);
+            }
+            _ if self.touch != Some(id) => {}
+            TouchPhase::Moved => {
+                let gesture = self.gestures.moved(position);
+                self.gesture(gesture);
+            }
+            TouchPhase::Ended => {
+                self.touch = None;
+                let gesture = self.gestures.release(position);
+                self.gesture(gesture);
+            }
+            TouchPhase::Cancelled => {
+                self.touch = None;
+                self.gestures.cancel();
+            }
+            _ => {}
+        }
     }
 
     fn update(&mut self) {
@@ -82,6 +153,18 @@ impl EventHandlerFree for SnakeGame {
                     _ => {}
                 };
             }
+            stages::NextStage::Replace(mut new_stage) => {
+                stage.exit(&mut self.renderer);
+                self.stages.pop().expect("Replaced an Empty StageStack");
+                new_stage.enter(&mut self.renderer);
+                self.stages.push(new_stage);
+            }
+            stages::NextStage::Quit => {
+                stage.exit(&mut self.renderer);
+                self.stages.clear();
+                self.renderer.ctx.request_quit();
+                return;
+            }
             _ => {}
         };
 
@@ -90,7 +173,13 @@ impl EventHandlerFree for SnakeGame {
     }
 
     fn draw(&mut self) {
-        for stage in self.stages.iter_mut() {
+        // Draw from the topmost full screen stage up through its overlays
+        let base = self
+            .stages
+            .iter()
+            .rposition(|stage| !stage.is_overlay())
+            .unwrap_or(0);
+        for stage in self.stages[base..].iter_mut() {
             stage.draw(&mut self.renderer);
         }
         let ctx = &mut self.renderer.ctx;
@@ -106,24 +195,28 @@ impl EventHandlerFree for SnakeGame {
             return;
         }
 
-        match keycode {
-            KeyCode::Left | KeyCode::A => {
-                self.input.go_left = true;
-            }
-            KeyCode::Right | KeyCode::D => {
-                self.input.go_right = true;
-            }
-            KeyCode::Up | KeyCode::W => {
-                self.input.go_up = true;
-            }
-            KeyCode::Down | KeyCode::S => {
-                self.input.go_down = true;
+        let mut settings = self.settings.borrow_mut();
+        // The settings menu is waiting for a key to bind, back gives up on it
+        if let Some(action) = settings.rebinding {
+            if settings.bindings.action(keycode) == Some(controls::Action::Back) {
+                settings.rebinding = None;
+            } else if controls::key_name(keycode).is_some() {
+                settings.rebinding = None;
+                // Left as it was if the key is some other action's last one
+                settings.bindings.set(action, &[keycode]);
             }
-            KeyCode::Escape => {
-                self.input.pause = true;
-                self.input.go_back = true;
-            }
-            _ => {}
+            return;
+        }
+
+        if let Some(action) = settings.bindings.action(keycode) {
+            self.input.act(action);
+        }
+    }
+
+    fn key_up_event(&mut self, keycode: KeyCode, _keymods: KeyMods) {
+        let settings = self.settings.borrow();
+        if settings.bindings.action(keycode) == Some(controls::Action::Boost) {
+            self.input.boosting = false;
         }
     }
 }
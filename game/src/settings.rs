@@ -0,0 +1,256 @@
+//! Player preferences, kept in `settings.cfg` in the data directory and
+//! changed from the settings menu. Uses the same `key = value` lines as the
+//! launch config.
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::components::Player;
+use crate::controls::{self, Action, Bindings};
+use crate::storage;
+use crate::types::{colors, Color};
+use snake::arena::{Arena, EdgeRule};
+use snake::gesture::Thresholds;
+
+pub const SETTINGS_FILE: &str = "settings.cfg";
+
+/// Slowest and fastest game speed the settings allow.
+pub const SPEED_RANGE: (f64, f64) = (0.5, 2.);
+
+/// Settings shared between the main loop, which maps keys with them, and the
+/// stages that use or change them.
+pub type SharedSettings = Rc<RefCell<Settings>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Classic,
+    Pastel,
+    Mono,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Classic, Theme::Pastel, Theme::Mono];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Classic => "Classic",
+            Theme::Pastel => "Pastel",
+            Theme::Mono => "Mono",
+        }
+    }
+
+    pub fn player_color(&self, player: Player) -> Color {
+        let palette = match self {
+            Theme::Classic => [
+                colors::RAYWHITE,
+                colors::LIME,
+                colors::SKYBLUE,
+                colors::ORANGE,
+            ],
+            Theme::Pastel => [colors::PINK, colors::SKYBLUE, colors::BEIGE, colors::PURPLE],
+            Theme::Mono => [
+                colors::WHITE,
+                colors::LIGHTGRAY,
+                colors::GRAY,
+                colors::DARKGRAY,
+            ],
+        };
+        palette[player.0 % palette.len()]
+    }
+
+    pub fn food_color(&self) -> Color {
+        match self {
+            Theme::Classic => colors::PURPLE,
+            Theme::Pastel => colors::GOLD,
+            Theme::Mono => colors::LIGHTGRAY,
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Theme::ALL
+            .iter()
+            .find(|theme| theme.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub bindings: Bindings,
+    /// Multiplier on how fast the simulation runs.
+    pub speed: f64,
+    /// Arena picked in the menu, `None` until one is, to play on the rules'.
+    pub arena: Option<Arena>,
+    pub theme: Theme,
+    /// Draws an arrow ahead of every snake showing where it heads.
+    pub debug_arrows: bool,
+    pub show_seed: bool,
+    /// How far and fast touch and mouse strokes have to go to swipe or tap.
+    pub gestures: Thresholds,
+    /// Action waiting for the next key press to be bound to it.
+    pub rebinding: Option<Action>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            bindings: Bindings::default(),
+            speed: 1.,
+            arena: None,
+            theme: Theme::Classic,
+            debug_arrows: true,
+            show_seed: true,
+            gestures: Thresholds::default(),
+            rebinding: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn path() -> PathBuf {
+        storage::data_dir()
+            .map(|dir| dir.join(SETTINGS_FILE))
+            .unwrap_or_else(|| PathBuf::from(SETTINGS_FILE))
+    }
+
+    /// The saved settings, with defaults for anything missing or unreadable.
+    pub fn load() -> Self {
+        let mut settings = Settings::default();
+        if let Ok(contents) = std::fs::read_to_string(Settings::path()) {
+            settings.apply_file(&contents);
+        }
+        settings
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = Settings::path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.serialize())
+    }
+
+    pub fn shared(self) -> SharedSettings {
+        Rc::new(RefCell::new(self))
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.max(SPEED_RANGE.0).min(SPEED_RANGE.1);
+    }
+
+    fn apply_file(&mut self, contents: &str) {
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                self.set(key.trim(), value.trim());
+            }
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        let flag = |value: &str| match value {
+            "true" | "on" | "1" => Some(true),
+            "false" | "off" | "0" => Some(false),
+            _ => None,
+        };
+        match key {
+            "speed" => {
+                if let Ok(speed) = f64::from_str(value) {
+                    self.set_speed(speed);
+                }
+            }
+            "arena_width" => {
+                if let Ok(width) = i32::from_str(value) {
+                    let arena = self.arena.unwrap_or_default();
+                    self.arena = Some(Arena::new(width, arena.height, arena.edge));
+                }
+            }
+            "arena_height" => {
+                if let Ok(height) = i32::from_str(value) {
+                    let arena = self.arena.unwrap_or_default();
+                    self.arena = Some(Arena::new(arena.width, height, arena.edge));
+                }
+            }
+            "edges" => {
+                if let Ok(edge) = EdgeRule::from_str(value) {
+                    let arena = self.arena.unwrap_or_default();
+                    self.arena = Some(Arena::new(arena.width, arena.height, edge));
+                }
+            }
+            "theme" => {
+                if let Ok(theme) = Theme::from_str(value) {
+                    self.theme = theme;
+                }
+            }
+            "debug_arrows" => self.debug_arrows = flag(value).unwrap_or(self.debug_arrows),
+            "show_seed" => self.show_seed = flag(value).unwrap_or(self.show_seed),
+            "swipe_distance" => {
+                if let Ok(distance) = f32::from_str(value) {
+                    self.gestures.swipe_distance = distance.max(1.);
+                }
+            }
+            "swipe_time" => {
+                if let Ok(time) = f64::from_str(value) {
+                    self.gestures.swipe_time = time.max(0.);
+                }
+            }
+            "tap_distance" => {
+                if let Ok(distance) = f32::from_str(value) {
+                    self.gestures.tap_distance = distance.max(0.);
+                }
+            }
+            "tap_time" => {
+                if let Ok(time) = f64::from_str(value) {
+                    self.gestures.tap_time = time.max(0.);
+                }
+            }
+            _ => {
+                let action = match key.strip_prefix("bind.").map(Action::from_str) {
+                    Some(Ok(action)) => action,
+                    _ => return,
+                };
+                let keys: Vec<_> = value
+                    .split(',')
+                    .filter_map(|name| controls::parse_key(name.trim()))
+                    .collect();
+                self.bindings.replace(action, &keys);
+            }
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "speed = {}", self.speed);
+        if let Some(arena) = self.arena {
+            let _ = writeln!(out, "arena_width = {}", arena.width);
+            let _ = writeln!(out, "arena_height = {}", arena.height);
+            let _ = writeln!(out, "edges = {}", arena.edge);
+        }
+        let _ = writeln!(out, "theme = {}", self.theme.name());
+        let _ = writeln!(out, "debug_arrows = {}", self.debug_arrows);
+        let _ = writeln!(out, "show_seed = {}", self.show_seed);
+        let gestures = &self.gestures;
+        let _ = writeln!(out, "swipe_distance = {}", gestures.swipe_distance);
+        let _ = writeln!(out, "swipe_time = {}", gestures.swipe_time);
+        let _ = writeln!(out, "tap_distance = {}", gestures.tap_distance);
+        let _ = writeln!(out, "tap_time = {}", gestures.tap_time);
+        for action in controls::actions() {
+            let keys: Vec<&str> = self
+                .bindings
+                .keys_for(action)
+                .filter_map(controls::key_name)
+                .collect();
+            let _ = writeln!(out, "bind.{} = {}", action, keys.join(", "));
+        }
+        out
+    }
+}
@@ -3,134 +3,452 @@ use graphics::renderer;
 
 use crate::{components, types};
 use crate::graphics::{self};
-use crate::stages::{NextStage, Paused, Stage};
+use crate::graphics::systems::RenderSystem;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::stages::{GameOver, NextStage, PauseChoice, Paused, Stage, Title};
 use crate::systems::{self, GameWorld};
+use crate::config::Config;
+use crate::controls;
+use crate::storage;
+use crate::timer::DateTime;
+use snake::ai;
+use snake::clock::{self, GameClock};
+use snake::food::FoodKind;
+use snake::powerup::PowerUp;
+use snake::input::Action;
+use snake::replay::{Playback, Recorder};
+use snake::rules::RuleSet;
+use snake::schedule::Schedule;
+use snake::scores::ScoreEntry;
+use snake::systems::PlayerResult;
+
+/// Where the most recently finished game is written.
+pub const LAST_REPLAY: &str = "replays/last.replay";
+
+/// How much faster the game runs while boost is held.
+const BOOST_SCALE: f64 = 2.;
 
 pub struct GameState {
-    direction: components::Direction,
     game_world: GameWorld,
-    move_timer: components::Timer,
-    food_timer: components::Timer,
-    score: i32,
+    recorder: Option<Recorder>,
+    /// Turns pressed since the last simulation tick.
+    pending: Input,
+    config: Config,
+    /// Left behind by the pause menu for when the game is entered again.
+    pause_choice: Rc<Cell<PauseChoice>>,
+    quit_to_menu: bool,
+    render: Schedule<RenderSystem>,
 }
 
 impl GameState {
-    pub fn new(input: &components::Input, renderer: &mut renderer::MainRenderer) -> Self {
-        let mut game_world = GameWorld {
-            events: Vec::with_capacity(32),
-            camera: components::Camera2D::new(input, 20.),
-            world: hecs::World::new(),
-        };
+    pub fn new(
+        input: &components::Input,
+        renderer: &mut renderer::MainRenderer,
+        config: &Config,
+    ) -> Self {
+        let settings = config.settings.borrow();
+        let mut clock = GameClock::new(Box::new(DateTime), clock::DEFAULT_STEP);
+        clock.set_scale(settings.speed);
+        let initial_seed = config
+            .seed
+            .unwrap_or_else(|| (miniquad::date::now() * 1000.) as u64);
+        let mut game_world =
+            GameWorld::new(components::Camera2D::new(input, 20.), clock, initial_seed);
+        game_world.fixed_seed = config.seed;
+        let arena = config.arena();
+        game_world.set_rules(RuleSet {
+            arena,
+            ..config.rules
+        });
+        game_world.level = config.level.clone();
+        game_world.set_player_count(config.players);
+        for (state, name) in game_world.players.iter_mut().zip(config.controllers.iter()) {
+            state.controller = name.as_deref().and_then(ai::controller);
+        }
+        game_world
+            .camera
+            .fit_arena(&arena, input.width, input.height);
+        systems::create_level_system(&mut game_world);
         systems::create_snake_system(&mut game_world);
-        let (load_cmd, text_component) =
-            components::Text::create_text(format!("Score:  {}", 0).as_str());
 
+        let hud_x = arena.left() as f32;
+        let hud_y = arena.top() as f32;
+        let players: Vec<components::Player> = game_world
+            .players
+            .iter()
+            .map(|state| state.player)
+            .collect();
+        for player in players.iter() {
+            let (load_cmd, text_component) =
+                graphics::systems::create_text(format!("Score:  {}", 0).as_str());
+            let offset = 12. * player.0 as f32;
+            game_world.world.spawn((
+                components::Score(*player),
+                components::Position(glam::Vec2::new(hud_x + offset, hud_y + 4.)),
+                text_component,
+            ));
+            renderer.asset_commands.push(load_cmd);
+
+            let (load_cmd, text_component) = graphics::systems::create_text("No power-ups");
+            game_world.world.spawn((
+                components::PowerUps(*player),
+                components::Position(glam::Vec2::new(hud_x + offset, hud_y + 2.)),
+                text_component,
+            ));
+            renderer.asset_commands.push(load_cmd);
+        }
+
+        let (load_cmd, text_component) = graphics::systems::create_text(
+            format!("Speed:  {:.1}/s", 1. / game_world.move_interval).as_str(),
+        );
         game_world.world.spawn((
-            components::Score,
-            components::Position(glam::Vec2::new(-24., 18.)),
+            components::Speed,
+            components::Position(glam::Vec2::new(arena.right() as f32 - 10., hud_y + 4.)),
             text_component,
         ));
         renderer.asset_commands.push(load_cmd);
 
-        let snake_texture = crate::utils::build_square_texture(
-            &mut renderer.ctx,
-            4,
-            types::colors::RAYWHITE,
-        );
-        let tail_texture = crate::utils::build_square_texture(
-            &mut renderer.ctx,
-            4,
-            types::colors::RAYWHITE,
-        );
-        let food_texture = crate::utils::build_square_texture(
+        if settings.show_seed {
+            let (load_cmd, text_component) =
+                graphics::systems::create_text(format!("Seed:  {}", initial_seed).as_str());
+            game_world.world.spawn((
+                components::Seed,
+                components::Position(glam::Vec2::new(hud_x, hud_y + 3.)),
+                text_component,
+            ));
+            renderer.asset_commands.push(load_cmd);
+        }
+
+        for player in players.iter() {
+            let color = settings.theme.player_color(*player);
+            let snake_texture = crate::utils::build_square_texture(&mut renderer.ctx, 4, color);
+            let tail_texture = crate::utils::build_square_texture(&mut renderer.ctx, 4, color);
+            renderer.add_material(player.material("Snake").0, vec![snake_texture]);
+            renderer.add_material(player.material("Tail").0, vec![tail_texture]);
+        }
+        for kind in FoodKind::ALL.iter() {
+            let spec = kind.spec();
+            let color = match kind {
+                FoodKind::Plain => settings.theme.food_color(),
+                _ => spec.color,
+            };
+            let texture = crate::utils::build_square_texture(&mut renderer.ctx, 4, color);
+            renderer.add_material(spec.material, vec![texture]);
+        }
+        for power in PowerUp::ALL.iter() {
+            let texture = crate::utils::build_square_texture(&mut renderer.ctx, 4, power.color());
+            renderer.add_material(power.material().0, vec![texture]);
+        }
+        let arrow_texture =
+            crate::utils::build_square_texture(&mut renderer.ctx, 4, types::colors::RED);
+
+        let wall_texture =
+            crate::utils::build_square_texture(&mut renderer.ctx, 4, types::colors::GRAY);
+
+        let dim_texture = crate::utils::build_square_texture(
             &mut renderer.ctx,
             4,
-            types::colors::PURPLE,
+            types::Color::from((0, 0, 0, 160)),
         );
-        let arrow_texture =
-            crate::utils::build_square_texture(&mut renderer.ctx, 4, types::colors::RED);
 
-        renderer.add_material("Snake", vec![snake_texture]);
-        renderer.add_material("Food", vec![food_texture]);
-        renderer.add_material("Tail", vec![tail_texture]);
         renderer.add_material("Arrow", vec![arrow_texture]);
+        renderer.add_material("Wall", vec![wall_texture]);
+        renderer.add_material("Dim", vec![dim_texture]);
 
         let snake_mesh = crate::utils::make_square_raw(1.);
         let food_mesh = crate::utils::make_square_raw(0.8);
+        let pickup_mesh = crate::utils::make_square_raw(0.6);
+        let wall_mesh = crate::utils::make_square_raw(1.);
         let tail_mesh = crate::utils::make_square_raw(0.8);
         let arrow_mesh = crate::utils::make_arrow_raw();
+        // Large enough to cover the arena and its margin at any aspect ratio
+        let dim_mesh =
+            crate::utils::make_rectangle_raw(arena.width as f32 * 2., arena.height as f32 * 2.);
 
         renderer.add_mesh("Food", &food_mesh.0, &food_mesh.1);
+        renderer.add_mesh("Pickup", &pickup_mesh.0, &pickup_mesh.1);
+        renderer.add_mesh("Wall", &wall_mesh.0, &wall_mesh.1);
         renderer.add_mesh("Tail", &tail_mesh.0, &tail_mesh.1);
         renderer.add_mesh("Snake", &snake_mesh.0, &snake_mesh.1);
         renderer.add_mesh("Arrow", &arrow_mesh.0, &arrow_mesh.1);
+        renderer.add_mesh("Dim", &dim_mesh.0, &dim_mesh.1);
 
         GameState {
-            direction: components::Direction::Up,
+            pending: Input::default(),
+            recorder: Some(Recorder::new(
+                initial_seed,
+                game_world.rules,
+                game_world.level.clone(),
+                game_world.players.len(),
+            )),
             game_world,
-            move_timer: components::Timer::new(0.25),
-            food_timer: components::Timer::new(1.5),
-            score: 0,
+            config: config.clone(),
+            pause_choice: Rc::new(Cell::new(PauseChoice::Resume)),
+            quit_to_menu: false,
+            render: graphics::systems::render_schedule(),
         }
     }
+
+    /// A game that is driven by recorded input instead of being recorded.
+    pub fn replaying(
+        input: &components::Input,
+        renderer: &mut renderer::MainRenderer,
+        playback: &Playback,
+    ) -> Self {
+        let config = Config {
+            seed: Some(playback.seed()),
+            rules: playback.rules(),
+            level: playback.level().clone(),
+            players: playback.players(),
+            ..Default::default()
+        };
+        let mut state = GameState::new(input, renderer, &config);
+        state.recorder = None;
+        state
+    }
+
+    pub fn game_world_mut(&mut self) -> &mut GameWorld {
+        &mut self.game_world
+    }
+
+    /// Steers the first player's snake towards a tapped point on the screen,
+    /// turning off the axis it is moving along.
+    fn turn_towards(&mut self, tap: glam::Vec2, width: f32, height: f32) {
+        use components::Direction;
+
+        let GameWorld {
+            world,
+            camera,
+            players,
+            ..
+        } = &self.game_world;
+        if players.get(0).map_or(true, |state| state.controller.is_some()) {
+            return;
+        }
+        let head = world
+            .query::<(
+                &components::Snake,
+                &components::Player,
+                &components::Position,
+                &components::HeadDirection,
+            )>()
+            .iter()
+            .find(|(_, (_, player, _, _))| player.0 == 0)
+            .map(|(_, (_, _, pos, heading))| (pos.0, heading.0));
+        let (head, heading) = match head {
+            Some(head) => head,
+            _ => return,
+        };
+        let delta = camera.screen_to_world(tap, width, height) - head;
+        let direction = match heading {
+            Direction::Up | Direction::Down if delta.x < 0. => Direction::Left,
+            Direction::Up | Direction::Down => Direction::Right,
+            _ if delta.y < 0. => Direction::Down,
+            _ => Direction::Up,
+        };
+        self.pending.press_for(components::Player(0), direction);
+    }
+
+    /// Runs the clock at the speed from the settings, faster while boosting.
+    pub fn set_boost(&mut self, boosting: bool) {
+        let speed = self.config.settings.borrow().speed;
+        let boost = if boosting { BOOST_SCALE } else { 1. };
+        self.game_world.clock.set_scale(speed * boost);
+    }
+
+    pub fn resize(&mut self, width: f32, height: f32) {
+        let GameWorld { camera, arena, .. } = &mut self.game_world;
+        camera.fit_arena(arena, width, height);
+    }
+
+    /// Feeds `input`, plus whatever the AI players pick, to the snakes and
+    /// runs one simulation tick. Returns true when the game ended.
+    pub fn tick(&mut self, input: &Input) -> bool {
+        self.game_world.input = *input;
+        let ended = systems::tick_systems(&mut self.game_world);
+        // Recorded after the tick, so it includes the AI players' turns
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(&self.game_world.last_input);
+        }
+        if ended {
+            if let Some(recorder) = &self.recorder {
+                if let Err(err) = recorder.replay().save(LAST_REPLAY) {
+                    eprintln!("Failed to save replay: {:?}", err);
+                }
+            }
+        }
+        ended
+    }
+
+    /// Files the human players' results in the high score table for this
+    /// mode. Returns a line for the results screen if any of them made it.
+    fn record_high_scores(&self, results: &[PlayerResult]) -> Option<String> {
+        let mut store = storage::high_score_store();
+        let mut scores = match store.load() {
+            Ok(scores) => scores,
+            Err(err) => {
+                eprintln!("Failed to load high scores: {:?}", err);
+                return None;
+            }
+        };
+        let mode = self.config.mode.key();
+        let timestamp = miniquad::date::now() as u64;
+        let mut best: Option<usize> = None;
+        for result in results {
+            let human = self
+                .game_world
+                .players
+                .get(result.player.0)
+                .map_or(false, |state| state.controller.is_none());
+            if !human {
+                continue;
+            }
+            let name = if results.len() > 1 {
+                format!("{} P{}", self.config.name, result.player.0 + 1)
+            } else {
+                self.config.name.clone()
+            };
+            let entry = ScoreEntry {
+                name,
+                score: result.score,
+                length: result.length,
+                seed: self.game_world.rng.seed(),
+                timestamp,
+            };
+            if let Some(rank) = scores.insert(mode, entry) {
+                best = Some(best.map_or(rank, |best| best.min(rank)));
+            }
+        }
+        let rank = best?;
+        if let Err(err) = store.save(&scores) {
+            eprintln!("Failed to save high scores: {:?}", err);
+        }
+        Some(format!("New high score!  #{}", rank + 1))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.game_world.outcome.is_some()
+    }
+
+    /// Starts the next game, recording it too if this one was recorded.
+    pub fn restart(&mut self) {
+        systems::restart_system(&mut self.game_world);
+        self.pending = Input::default();
+        if let Some(recorder) = &mut self.recorder {
+            let GameWorld {
+                rng,
+                rules,
+                level,
+                players,
+                ..
+            } = &self.game_world;
+            *recorder = Recorder::new(rng.seed(), *rules, level.clone(), players.len());
+        }
+    }
+
+    pub fn sync_assets(&mut self, renderer: &mut graphics::MainRenderer) {
+        graphics::systems::sync_text_system(&mut self.game_world, &mut renderer.asset_commands);
+    }
 }
 
 impl Stage for GameState {
+    /// Acts on the pause menu's or results screen's choice, and starts the
+    /// next game once the results of a finished one were dismissed.
     fn enter(&mut self, _: &mut graphics::MainRenderer) {
-        self.move_timer.resume();
-        self.food_timer.resume();
+        match self.pause_choice.replace(PauseChoice::Resume) {
+            PauseChoice::Restart => self.restart(),
+            PauseChoice::QuitToMenu => self.quit_to_menu = true,
+            PauseChoice::Resume => {}
+        }
+        if self.is_finished() && !self.quit_to_menu {
+            self.restart();
+        }
+        // The speed and arrows may have changed in the settings menu
+        self.set_boost(false);
+        let arrows = self.config.settings.borrow().debug_arrows;
+        graphics::systems::show_heading_arrows(&mut self.render, arrows);
+        self.game_world.clock.resume();
     }
 
     fn exit(&mut self, _: &mut graphics::MainRenderer) {
-        self.move_timer.paused();
-        self.food_timer.paused();
+        self.game_world.clock.pause();
     }
 
     fn update(&mut self, input: &Input, renderer: &mut graphics::MainRenderer) -> NextStage {
         let input = input.clone();
         if input.resized {
             let Input { width, height, .. } = input;
-            self.game_world.camera.resize(width, height);
-        }
-        if input.pause {
-            return NextStage::Push(Box::new(Paused::new()));
-        }
-        self.direction.update(&input);
-        systems::update_input(&mut self.game_world, &input);
-        if self.move_timer.finished() {
-            crate::systems::update_velocity_direction(&mut self.game_world);
-            systems::tail_movement_system(&mut self.game_world);
-            systems::head_collision_system(&mut self.game_world);
-            systems::handle_collision_system(&mut self.game_world);
-            systems::trigger_tail_spawn(&mut self.game_world);
-            systems::movement_system(&mut self.game_world);
-            systems::spawn_tail_system(&mut self.game_world);
-            self.move_timer.reset();
-        }
-        if self.food_timer.finished() {
-            systems::add_food_system(&mut self.game_world);
-            self.food_timer.reset();
-        }
-
-        systems::despawn_food_system(&mut self.game_world);
-        systems::update_score_system(
-            &mut self.game_world,
-            &mut self.score,
-            &mut renderer.asset_commands,
-        );
-        if systems::game_over_system(&mut self.game_world) {
-            self.move_timer.reset();
-            self.food_timer.reset();
+            self.resize(width, height);
+        }
+        if self.quit_to_menu {
+            return NextStage::Replace(Box::new(Title::new(&input, self.config.clone())));
+        }
+        if input.triggered(Action::Pause) || input.triggered(Action::Back) {
+            let paused = Paused::new(
+                self.pause_choice.clone(),
+                self.config.settings.clone(),
+                self.game_world.camera,
+            );
+            return NextStage::Push(Box::new(paused));
+        }
+        let players = self.game_world.players.len();
+        for (layout, turn) in input.turns() {
+            let player = match controls::seat(layout, players) {
+                Some(player) => player,
+                _ => continue,
+            };
+            let ai_player = self
+                .game_world
+                .players
+                .get(player.0)
+                .map_or(false, |state| state.controller.is_some());
+            if ai_player {
+                continue;
+            }
+            self.pending.press_for(player, turn);
         }
+        if let Some(tap) = input.tap {
+            self.turn_towards(tap, input.width, input.height);
+        }
+        self.set_boost(input.boosting);
+        for _ in 0..self.game_world.clock.update() {
+            let tick_input = std::mem::take(&mut self.pending);
+            if self.tick(&tick_input) {
+                break;
+            }
+        }
+        self.sync_assets(renderer);
 
-        self.game_world.events.clear();
+        if let Some(outcome) = self.game_world.outcome {
+            let GameWorld { arena, .. } = &self.game_world;
+            let anchor = glam::Vec2::new(arena.left() as f32 + 4., arena.top() as f32 - 4.);
+            let results = self.game_world.results();
+            let seed = self.game_world.rng.seed();
+            let note = self.record_high_scores(&results);
+            let game_over = GameOver::new(
+                outcome,
+                &results,
+                seed,
+                note,
+                &self.config.settings.borrow().bindings,
+                self.pause_choice.clone(),
+                anchor,
+            );
+            return NextStage::Push(Box::new(game_over));
+        }
         NextStage::Noop
     }
 
     fn draw(&mut self, renderer: &mut graphics::MainRenderer) {
         renderer.update_view(&self.game_world.camera);
-        systems::gather_render_cmds(&mut self.game_world, renderer);
-        systems::debug_render_cmds(&mut self.game_world, renderer);
-        systems::draw_text(&mut self.game_world, renderer);
+        let systems = self
+            .render
+            .systems()
+            .expect("The render schedule has conflicting constraints");
+        for system in systems {
+            system(&mut self.game_world, renderer);
+        }
     }
 }
@@ -0,0 +1,139 @@
+use glam::Vec2;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::{
+    components::{Input, Player, Text},
+    controls::Bindings,
+    graphics,
+    stages::{NextStage, PauseChoice, Stage},
+};
+use snake::events::DeathCause;
+use snake::input::Action;
+use snake::systems::{Outcome, PlayerResult};
+
+/// World units between two lines of the results screen.
+const LINE_HEIGHT: f32 = 2.;
+
+/// Results screen shown over the frozen last frame of a finished game.
+/// Confirm starts the next game, going back returns to the title.
+pub struct GameOver {
+    lines: Vec<Text>,
+    anchor: Vec2,
+    /// Shared with the game underneath, which leaves for the title on
+    /// `QuitToMenu`.
+    choice: Rc<Cell<PauseChoice>>,
+}
+
+impl GameOver {
+    /// `note` is an extra line under the headline, such as a new high score.
+    /// `seed` is shown so the game can be played again.
+    pub fn new(
+        outcome: Outcome,
+        results: &[PlayerResult],
+        seed: u64,
+        note: Option<String>,
+        bindings: &Bindings,
+        choice: Rc<Cell<PauseChoice>>,
+        anchor: Vec2,
+    ) -> Self {
+        let mut lines = vec![Text::new(&headline(outcome, results))];
+        if let Some(note) = note {
+            lines.push(Text::new(&note));
+        }
+        for result in results {
+            lines.push(Text::new(&summary(result, results.len() > 1)));
+        }
+        lines.push(Text::new(&format!("Seed: {}", seed)));
+        lines.push(Text::new(&format!(
+            "{}: play again    {}: menu",
+            bindings.key_name(Action::Confirm),
+            bindings.key_name(Action::Back)
+        )));
+        Self {
+            lines,
+            anchor,
+            choice,
+        }
+    }
+}
+
+fn headline(outcome: Outcome, results: &[PlayerResult]) -> String {
+    let survivor = results.iter().find(|result| result.cause.is_none());
+    match (outcome, survivor) {
+        (Outcome::Win, _) => "Board cleared!".to_string(),
+        (Outcome::GameOver, Some(winner)) if results.len() > 1 => {
+            format!("Player {} wins!", winner.player.0 + 1)
+        }
+        _ => "Game Over".to_string(),
+    }
+}
+
+fn summary(result: &PlayerResult, label: bool) -> String {
+    let PlayerResult {
+        player,
+        score,
+        length,
+        survived,
+        cause,
+    } = result;
+    let mut line = format!(
+        "Score: {}   Length: {}   Time: {:.1}s",
+        score, length, survived
+    );
+    if let Some(cause) = cause {
+        line.push_str("   ");
+        line.push_str(&describe(*player, cause));
+    }
+    if label {
+        line = format!("P{}  {}", player.0 + 1, line);
+    }
+    line
+}
+
+fn describe(player: Player, cause: &DeathCause) -> String {
+    match cause {
+        DeathCause::Wall => "Hit a wall".to_string(),
+        DeathCause::Tail { owner } if *owner == player => "Bit its own tail".to_string(),
+        DeathCause::Tail { owner } => format!("Ran into P{}", owner.0 + 1),
+        DeathCause::HeadOn { other } => format!("Head on with P{}", other.0 + 1),
+    }
+}
+
+impl Stage for GameOver {
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn enter(&mut self, renderer: &mut graphics::MainRenderer) {
+        for line in self.lines.iter() {
+            renderer
+                .asset_commands
+                .push(graphics::systems::load_text_command(line));
+        }
+    }
+
+    fn update(&mut self, input: &Input, _renderer: &mut graphics::MainRenderer) -> NextStage {
+        if input.triggered(Action::Confirm) || input.tap.is_some() {
+            return NextStage::Pop;
+        }
+        if input.triggered(Action::Back) {
+            self.choice.set(PauseChoice::QuitToMenu);
+            return NextStage::Pop;
+        }
+        NextStage::Noop
+    }
+
+    fn draw(&mut self, renderer: &mut graphics::MainRenderer) {
+        let commands = &mut renderer.main_render_target.commands;
+        for (index, line) in self.lines.iter().enumerate() {
+            commands.push(graphics::renderer::RenderCommand::DrawFont(
+                graphics::renderer::DrawFont {
+                    text: line.text(),
+                    font: line.font().into(),
+                    position: self.anchor - Vec2::new(0., index as f32 * LINE_HEIGHT),
+                },
+            ));
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use glam::Vec2;
+
+use crate::{
+    components::{Camera2D, Direction, Input, Text},
+    config::{Config, Mode},
+    graphics,
+    stages::{NextStage, Stage, Title},
+    storage,
+};
+use snake::input::Action;
+
+/// World units between two lines of the table.
+const LINE_HEIGHT: f32 = 2.;
+
+/// The saved high scores for one mode at a time. Left and right switch
+/// modes, back returns to the title.
+pub struct HighScoreTable {
+    config: Config,
+    mode: usize,
+    camera: Camera2D,
+    lines: Vec<Text>,
+}
+
+impl HighScoreTable {
+    pub fn new(input: &Input, config: Config, mode: Mode) -> Self {
+        let mut camera = Camera2D::new(input, 20.);
+        camera.fit_arena(&config.arena(), input.width, input.height);
+        let mut table = Self {
+            mode: Mode::ALL.iter().position(|m| *m == mode).unwrap_or(0),
+            config,
+            camera,
+            lines: Vec::new(),
+        };
+        table.refresh();
+        table
+    }
+
+    fn refresh(&mut self) {
+        let mode = Mode::ALL[self.mode];
+        let scores = match storage::high_score_store().load() {
+            Ok(scores) => scores,
+            Err(err) => {
+                eprintln!("Failed to load high scores: {:?}", err);
+                Default::default()
+            }
+        };
+        self.lines = vec![Text::new(&format!("High Scores:  {}", mode.name()))];
+        let entries = scores.top(mode.key());
+        if entries.is_empty() {
+            self.lines.push(Text::new("Nothing here yet"));
+        }
+        for (rank, entry) in entries.iter().enumerate() {
+            self.lines.push(Text::new(&format!(
+                "{:>2}.  {}   {}   Length: {}   Seed: {}",
+                rank + 1,
+                entry.score,
+                entry.name,
+                entry.length,
+                entry.seed
+            )));
+        }
+    }
+
+    fn load_lines(&self, renderer: &mut graphics::MainRenderer) {
+        for line in self.lines.iter() {
+            renderer
+                .asset_commands
+                .push(graphics::systems::load_text_command(line));
+        }
+    }
+}
+
+impl Stage for HighScoreTable {
+    fn enter(&mut self, renderer: &mut graphics::MainRenderer) {
+        self.load_lines(renderer);
+    }
+
+    fn update(&mut self, input: &Input, renderer: &mut graphics::MainRenderer) -> NextStage {
+        if input.resized {
+            let Input { width, height, .. } = *input;
+            self.camera.fit_arena(&self.config.arena(), width, height);
+        }
+        let confirmed = input.triggered(Action::Confirm) || input.tap.is_some();
+        if input.triggered(Action::Back) || confirmed {
+            return NextStage::Replace(Box::new(Title::new(input, self.config.clone())));
+        }
+        let modes = Mode::ALL.len();
+        let previous = self.mode;
+        if input.pressed(Direction::Left) {
+            self.mode = (self.mode + modes - 1) % modes;
+        }
+        if input.pressed(Direction::Right) {
+            self.mode = (self.mode + 1) % modes;
+        }
+        if self.mode != previous {
+            self.refresh();
+            self.load_lines(renderer);
+        }
+        NextStage::Noop
+    }
+
+    fn draw(&mut self, renderer: &mut graphics::MainRenderer) {
+        renderer.update_view(&self.camera);
+        let commands = &mut renderer.main_render_target.commands;
+        for (index, line) in self.lines.iter().enumerate() {
+            commands.push(graphics::renderer::RenderCommand::DrawFont(
+                graphics::renderer::DrawFont {
+                    text: line.text(),
+                    font: line.font().into(),
+                    position: Vec2::new(-16., 10. - index as f32 * LINE_HEIGHT),
+                },
+            ));
+        }
+    }
+}
@@ -1,9 +1,37 @@
 pub mod game;
+pub mod game_over;
+pub mod high_scores;
 pub mod paused;
+pub mod replay;
+pub mod settings;
+pub mod title;
 
-use crate::{components::Input, graphics};
+use glam::Vec2;
+
+use crate::{
+    components::{Camera2D, Input},
+    graphics,
+};
 pub use game::GameState;
-pub use paused::Paused;
+pub use game_over::GameOver;
+pub use high_scores::HighScoreTable;
+pub use paused::{PauseChoice, Paused};
+pub use replay::ReplayState;
+pub use settings::SettingsMenu;
+pub use title::Title;
+
+/// Index of the menu line a tap landed on, given where each line is drawn
+/// and how far apart they are.
+pub fn tapped_line<I>(input: &Input, camera: &Camera2D, lines: I, line_height: f32) -> Option<usize>
+where
+    I: IntoIterator<Item = Vec2>,
+{
+    let tap = input.tap?;
+    let world = camera.screen_to_world(tap, input.width, input.height);
+    lines.into_iter().position(|line| {
+        world.x >= line.x - line_height && (world.y - line.y).abs() <= line_height / 2.
+    })
+}
 
 pub type StageStack = Vec<Box<dyn Stage>>;
 
@@ -15,9 +43,18 @@ pub enum NextStage {
     Noop,
     Pop,
     Push(Box<dyn Stage>),
+    /// Swaps the current stage for another, so menus don't pile up.
+    Replace(Box<dyn Stage>),
+    /// Leaves every stage and closes the game.
+    Quit,
 }
 
 pub trait Stage {
+    /// Overlays are drawn over the stages below them, which stay visible but
+    /// aren't updated while the overlay is on top.
+    fn is_overlay(&self) -> bool {
+        false
+    }
     fn enter(&mut self, _renderer: &mut graphics::MainRenderer) {}
     fn exit(&mut self, _renderer: &mut graphics::MainRenderer) {}
     fn update(&mut self, input: &Input, _renderer: &mut graphics::MainRenderer) -> NextStage;
@@ -1,25 +1,176 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use glam::Vec2;
+
 use crate::{
-    components::Input,
+    components::{Camera2D, Direction, Input, Text},
     graphics,
-    stages::{NextStage, Stage},
+    settings::SharedSettings,
+    stages::{self, NextStage, SettingsMenu, Stage},
+    timer::DateTime,
 };
+use snake::clock::GameClock;
+use snake::input::Action;
+
+/// World units between two menu entries.
+const LINE_HEIGHT: f32 = 2.;
+const MARKER: &str = ">";
+/// Seconds counted down before the game picks up again.
+const COUNTDOWN: u32 = 3;
+
+/// What the game should do once the pause overlay is gone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseChoice {
+    Resume,
+    Restart,
+    QuitToMenu,
+}
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Resume,
+    Restart,
+    Settings,
+    QuitToMenu,
+}
+
+const ENTRIES: [Entry; 4] = [
+    Entry::Resume,
+    Entry::Restart,
+    Entry::Settings,
+    Entry::QuitToMenu,
+];
+
+fn label(entry: Entry) -> &'static str {
+    match entry {
+        Entry::Resume => "Resume",
+        Entry::Restart => "Restart",
+        Entry::Settings => "Settings",
+        Entry::QuitToMenu => "Quit to menu",
+    }
+}
+
+/// Where the entry at `index` is drawn.
+fn line(index: usize) -> Vec2 {
+    Vec2::new(-6., 4. - index as f32 * LINE_HEIGHT)
+}
+
+/// Menu drawn over the dimmed, frozen game. Resuming counts down from three
+/// before handing control back. The choice is left in `choice` for the stage
+/// below to act on when it is entered again.
 pub struct Paused {
-    go_back: bool,
+    choice: Rc<Cell<PauseChoice>>,
+    settings: SharedSettings,
+    /// The game's camera, which the menu is drawn with.
+    camera: Camera2D,
+    selected: usize,
+    /// Ticks once a second while counting down to resume.
+    countdown: Option<(GameClock, u32)>,
 }
 
 impl Paused {
-    pub fn new() -> Self {
-        Default::default()
+    pub fn new(choice: Rc<Cell<PauseChoice>>, settings: SharedSettings, camera: Camera2D) -> Self {
+        choice.set(PauseChoice::Resume);
+        Self {
+            choice,
+            settings,
+            camera,
+            selected: 0,
+            countdown: None,
+        }
+    }
+
+    fn activate(&mut self, entry: Entry, input: &Input) -> NextStage {
+        match entry {
+            Entry::Resume => {
+                self.countdown = Some((GameClock::new(Box::new(DateTime), 1.), COUNTDOWN));
+                NextStage::Noop
+            }
+            Entry::Restart => {
+                self.choice.set(PauseChoice::Restart);
+                NextStage::Pop
+            }
+            Entry::Settings => {
+                let menu = SettingsMenu::from_pause(input, self.settings.clone());
+                NextStage::Push(Box::new(menu))
+            }
+            Entry::QuitToMenu => {
+                self.choice.set(PauseChoice::QuitToMenu);
+                NextStage::Pop
+            }
+        }
     }
 }
 
 impl Stage for Paused {
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn enter(&mut self, renderer: &mut graphics::MainRenderer) {
+        let mut labels: Vec<String> = ENTRIES.iter().map(|entry| label(*entry).into()).collect();
+        labels.push(MARKER.into());
+        labels.extend((1..=COUNTDOWN).map(|second| second.to_string()));
+        for text in labels {
+            renderer
+                .asset_commands
+                .push(graphics::systems::load_text_command(&Text::new(&text)));
+        }
+    }
+
     fn update(&mut self, input: &Input, _renderer: &mut graphics::MainRenderer) -> NextStage {
-        if input.go_back {
-            return NextStage::Pop;
+        if let Some((clock, remaining)) = &mut self.countdown {
+            *remaining = remaining.saturating_sub(clock.update());
+            if *remaining == 0 {
+                return NextStage::Pop;
+            }
+            return NextStage::Noop;
+        }
+
+        if input.triggered(Action::Back) || input.triggered(Action::Pause) {
+            return self.activate(Entry::Resume, input);
+        }
+        let count = ENTRIES.len();
+        if input.pressed(Direction::Up) {
+            self.selected = (self.selected + count - 1) % count;
+        }
+        if input.pressed(Direction::Down) {
+            self.selected = (self.selected + 1) % count;
+        }
+        let lines = (0..count).map(line);
+        if let Some(index) = stages::tapped_line(input, &self.camera, lines, LINE_HEIGHT) {
+            self.selected = index;
+            return self.activate(ENTRIES[index], input);
+        }
+        if input.triggered(Action::Confirm) {
+            return self.activate(ENTRIES[self.selected], input);
         }
         NextStage::Noop
     }
+
+    fn draw(&mut self, renderer: &mut graphics::MainRenderer) {
+        let commands = &mut renderer.main_render_target.commands;
+        commands.push(graphics::systems::dim_command());
+
+        let mut lines: Vec<(String, Vec2)> = Vec::new();
+        match &self.countdown {
+            Some((_, remaining)) => lines.push((remaining.to_string(), Vec2::new(0., 0.))),
+            _ => {
+                for (index, entry) in ENTRIES.iter().enumerate() {
+                    lines.push((label(*entry).into(), line(index)));
+                }
+                lines.push((MARKER.into(), line(self.selected) - Vec2::new(2., 0.)));
+            }
+        }
+        for (text, position) in lines {
+            commands.push(graphics::renderer::RenderCommand::DrawFont(
+                graphics::renderer::DrawFont {
+                    text,
+                    font: "KenneyFuture".into(),
+                    position,
+                },
+            ));
+        }
+    }
 }
@@ -0,0 +1,60 @@
+use crate::{
+    components::Input,
+    graphics,
+    stages::{GameState, NextStage, Stage},
+};
+use snake::input::Action;
+use snake::replay::{Playback, Replay};
+
+/// Plays a recorded game back, feeding its inputs in place of the keyboard.
+pub struct ReplayState {
+    game: GameState,
+    playback: Playback,
+}
+
+impl ReplayState {
+    pub fn new(input: &Input, renderer: &mut graphics::MainRenderer, replay: Replay) -> Self {
+        let playback = Playback::new(replay);
+        Self {
+            game: GameState::replaying(input, renderer, &playback),
+            playback,
+        }
+    }
+}
+
+impl Stage for ReplayState {
+    fn enter(&mut self, renderer: &mut graphics::MainRenderer) {
+        self.game.enter(renderer);
+    }
+
+    fn exit(&mut self, renderer: &mut graphics::MainRenderer) {
+        self.game.exit(renderer);
+    }
+
+    fn update(&mut self, input: &Input, renderer: &mut graphics::MainRenderer) -> NextStage {
+        if input.resized {
+            self.game.resize(input.width, input.height);
+        }
+        if input.triggered(Action::Back) {
+            return NextStage::Pop;
+        }
+        self.game.set_boost(input.boosting);
+
+        // Once the recording runs out the last frame stays up until dismissed
+        for _ in 0..self.game.game_world_mut().clock.update() {
+            let recorded = match self.playback.next_input() {
+                Some(recorded) => recorded,
+                _ => break,
+            };
+            if self.game.tick(&recorded) {
+                break;
+            }
+        }
+        self.game.sync_assets(renderer);
+        NextStage::Noop
+    }
+
+    fn draw(&mut self, renderer: &mut graphics::MainRenderer) {
+        self.game.draw(renderer);
+    }
+}
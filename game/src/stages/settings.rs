@@ -0,0 +1,296 @@
+use glam::Vec2;
+
+use crate::{
+    components::{Camera2D, Direction, Input, Text},
+    config::Config,
+    controls::{self, Action},
+    graphics,
+    settings::{SharedSettings, Theme},
+    stages::{self, NextStage, Stage, Title},
+};
+use snake::arena::{Arena, EdgeRule};
+
+/// World units between two menu entries.
+const LINE_HEIGHT: f32 = 2.;
+/// Entries shown at once, the list scrolls to keep the selection in view.
+const VISIBLE: usize = 11;
+const MARKER: &str = ">";
+const SPEED_STEP: f64 = 0.25;
+const ARENA_STEP: i32 = 2;
+/// Smallest and largest arena side the menu allows.
+const ARENA_RANGE: (i32, i32) = (10, 80);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Speed,
+    Width,
+    Height,
+    Edges,
+    Theme,
+    Arrows,
+    Seed,
+    Bind(Action),
+    Back,
+}
+
+/// Key bindings, game speed, arena size, color theme and debug toggles. Left
+/// and right change the selected value, confirm on a binding waits for the
+/// next key. Everything is saved when the menu is left.
+pub struct SettingsMenu {
+    settings: SharedSettings,
+    /// Set when opened from the title, which it returns to. Opened from the
+    /// pause menu it pops back instead and the arena can't be changed.
+    title: Option<Config>,
+    /// Set once the arena is changed, so leaving doesn't touch it otherwise.
+    arena_changed: bool,
+    entries: Vec<Entry>,
+    selected: usize,
+    camera: Camera2D,
+    /// Lines already sent to the renderer, so only changed ones are loaded.
+    loaded: Vec<String>,
+}
+
+impl SettingsMenu {
+    pub fn from_title(input: &Input, config: Config) -> Self {
+        let settings = config.settings.clone();
+        SettingsMenu::new(input, settings, Some(config))
+    }
+
+    pub fn from_pause(input: &Input, settings: SharedSettings) -> Self {
+        SettingsMenu::new(input, settings, None)
+    }
+
+    fn new(input: &Input, settings: SharedSettings, title: Option<Config>) -> Self {
+        let mut camera = Camera2D::new(input, 20.);
+        camera.fit_arena(&Arena::default(), input.width, input.height);
+        let mut entries = vec![Entry::Speed];
+        if title.is_some() {
+            entries.extend_from_slice(&[Entry::Width, Entry::Height, Entry::Edges]);
+        }
+        entries.extend_from_slice(&[Entry::Theme, Entry::Arrows, Entry::Seed]);
+        entries.extend(controls::actions().into_iter().map(Entry::Bind));
+        entries.push(Entry::Back);
+        Self {
+            settings,
+            title,
+            arena_changed: false,
+            entries,
+            selected: 0,
+            camera,
+            loaded: Vec::new(),
+        }
+    }
+
+    /// The arena the next game plays on, as far as the menu knows.
+    fn arena(&self) -> Arena {
+        let picked = self.settings.borrow().arena;
+        match &self.title {
+            Some(config) => picked.unwrap_or_else(|| config.arena()),
+            _ => picked.unwrap_or_default(),
+        }
+    }
+
+    fn label(&self, entry: Entry) -> String {
+        let arena = self.arena();
+        let settings = self.settings.borrow();
+        let on_off = |on: bool| if on { "On" } else { "Off" };
+        match entry {
+            Entry::Speed => format!("Speed:  {:.2}x", settings.speed),
+            Entry::Width => format!("Arena width:  {}", arena.width),
+            Entry::Height => format!("Arena height:  {}", arena.height),
+            Entry::Edges => format!("Edges:  {}", arena.edge),
+            Entry::Theme => format!("Theme:  {}", settings.theme.name()),
+            Entry::Arrows => format!("Heading arrows:  {}", on_off(settings.debug_arrows)),
+            Entry::Seed => format!("Show seed:  {}", on_off(settings.show_seed)),
+            Entry::Bind(action) if settings.rebinding == Some(action) => {
+                format!("{}:  press a key", controls::label(action))
+            }
+            Entry::Bind(action) => {
+                let keys: Vec<&str> = settings
+                    .bindings
+                    .keys_for(action)
+                    .filter_map(controls::key_name)
+                    .collect();
+                format!("{}:  {}", controls::label(action), keys.join(", "))
+            }
+            Entry::Back => "Back".to_string(),
+        }
+    }
+
+    /// Index of the first entry shown.
+    fn scroll(&self) -> usize {
+        let last = self.entries.len().saturating_sub(VISIBLE);
+        self.selected.saturating_sub(VISIBLE / 2).min(last)
+    }
+
+    /// Indices of the entries shown.
+    fn visible(&self) -> std::ops::Range<usize> {
+        let scroll = self.scroll();
+        scroll..(scroll + VISIBLE).min(self.entries.len())
+    }
+
+    /// Where the entry at `index` is drawn, if it is in view.
+    fn line(&self, index: usize) -> Vec2 {
+        Vec2::new(-14., 10. - (index - self.scroll()) as f32 * LINE_HEIGHT)
+    }
+
+    fn lines(&self) -> Vec<(String, Vec2)> {
+        let mut lines: Vec<(String, Vec2)> = vec![("Settings".to_string(), Vec2::new(-14., 13.))];
+        for index in self.visible() {
+            lines.push((self.label(self.entries[index]), self.line(index)));
+        }
+        lines.push((
+            MARKER.to_string(),
+            self.line(self.selected) - Vec2::new(2., 0.),
+        ));
+        lines
+    }
+
+    fn load_lines(&mut self, renderer: &mut graphics::MainRenderer) {
+        for (text, _) in self.lines() {
+            if self.loaded.contains(&text) {
+                continue;
+            }
+            renderer
+                .asset_commands
+                .push(graphics::systems::load_text_command(&Text::new(&text)));
+            self.loaded.push(text);
+        }
+    }
+
+    /// Nudges the value of `entry` one step up or down.
+    fn change(&mut self, entry: Entry, up: bool) {
+        let arena = self.arena();
+        if let Entry::Width | Entry::Height | Entry::Edges = entry {
+            self.arena_changed = true;
+        }
+        let mut settings = self.settings.borrow_mut();
+        let sign = if up { 1 } else { -1 };
+        let resize = |side: i32| {
+            (side + sign * ARENA_STEP)
+                .max(ARENA_RANGE.0)
+                .min(ARENA_RANGE.1)
+        };
+        match entry {
+            Entry::Speed => {
+                let speed = settings.speed + sign as f64 * SPEED_STEP;
+                settings.set_speed(speed);
+            }
+            Entry::Width => {
+                settings.arena = Some(Arena::new(resize(arena.width), arena.height, arena.edge))
+            }
+            Entry::Height => {
+                settings.arena = Some(Arena::new(arena.width, resize(arena.height), arena.edge))
+            }
+            Entry::Edges => {
+                let edge = match arena.edge {
+                    EdgeRule::Walls => EdgeRule::Wrap,
+                    EdgeRule::Wrap => EdgeRule::Walls,
+                };
+                settings.arena = Some(Arena::new(arena.width, arena.height, edge))
+            }
+            Entry::Theme => {
+                let themes = Theme::ALL.len();
+                let current = Theme::ALL
+                    .iter()
+                    .position(|theme| *theme == settings.theme)
+                    .unwrap_or(0);
+                let next = if up {
+                    current + 1
+                } else {
+                    current + themes - 1
+                };
+                settings.theme = Theme::ALL[next % themes];
+            }
+            Entry::Arrows => settings.debug_arrows = !settings.debug_arrows,
+            Entry::Seed => settings.show_seed = !settings.show_seed,
+            Entry::Bind(_) | Entry::Back => {}
+        }
+    }
+
+    fn leave(&mut self, input: &Input) -> NextStage {
+        let settings = self.settings.borrow();
+        if let Err(err) = settings.save() {
+            eprintln!("Failed to save settings: {:?}", err);
+        }
+        match self.title.take() {
+            Some(mut config) => {
+                if self.arena_changed {
+                    config.arena = settings.arena;
+                }
+                NextStage::Replace(Box::new(Title::new(input, config)))
+            }
+            _ => NextStage::Pop,
+        }
+    }
+}
+
+impl Stage for SettingsMenu {
+    fn enter(&mut self, renderer: &mut graphics::MainRenderer) {
+        self.load_lines(renderer);
+    }
+
+    fn exit(&mut self, _renderer: &mut graphics::MainRenderer) {
+        self.settings.borrow_mut().rebinding = None;
+    }
+
+    fn update(&mut self, input: &Input, renderer: &mut graphics::MainRenderer) -> NextStage {
+        if input.resized {
+            let Input { width, height, .. } = *input;
+            self.camera.fit_arena(&Arena::default(), width, height);
+        }
+        // Keys are going to the binding being changed, a tap gives up on it
+        // as back does
+        if self.settings.borrow().rebinding.is_some() {
+            if input.tap.is_some() {
+                self.settings.borrow_mut().rebinding = None;
+                self.load_lines(renderer);
+            }
+            return NextStage::Noop;
+        }
+        if input.triggered(Action::Back) {
+            return self.leave(input);
+        }
+        let count = self.entries.len();
+        if input.pressed(Direction::Up) {
+            self.selected = (self.selected + count - 1) % count;
+        }
+        if input.pressed(Direction::Down) {
+            self.selected = (self.selected + 1) % count;
+        }
+        let visible = self.visible();
+        let lines = visible.clone().map(|index| self.line(index));
+        let tapped = stages::tapped_line(input, &self.camera, lines, LINE_HEIGHT);
+        if let Some(offset) = tapped {
+            self.selected = visible.start + offset;
+        }
+        let entry = self.entries[self.selected];
+        if input.pressed(Direction::Left) || input.pressed(Direction::Right) {
+            self.change(entry, input.pressed(Direction::Right));
+        }
+        if input.triggered(Action::Confirm) || tapped.is_some() {
+            match entry {
+                Entry::Bind(action) => self.settings.borrow_mut().rebinding = Some(action),
+                Entry::Back => return self.leave(input),
+                _ => self.change(entry, true),
+            }
+        }
+        self.load_lines(renderer);
+        NextStage::Noop
+    }
+
+    fn draw(&mut self, renderer: &mut graphics::MainRenderer) {
+        renderer.update_view(&self.camera);
+        let lines = self.lines();
+        let commands = &mut renderer.main_render_target.commands;
+        for (text, position) in lines {
+            commands.push(graphics::renderer::RenderCommand::DrawFont(
+                graphics::renderer::DrawFont {
+                    text,
+                    font: "KenneyFuture".into(),
+                    position,
+                },
+            ));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use glam::Vec2;
+
+use crate::{
+    components::{Camera2D, Direction, Input, Text},
+    config::{Config, Mode},
+    graphics,
+    stages::{self, GameState, HighScoreTable, NextStage, SettingsMenu, Stage},
+};
+use snake::input::Action;
+
+/// World units between two menu entries.
+const LINE_HEIGHT: f32 = 2.;
+const MARKER: &str = ">";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Play,
+    Mode,
+    Settings,
+    HighScores,
+    Quit,
+}
+
+const ENTRIES: [Entry; 5] = [
+    Entry::Play,
+    Entry::Mode,
+    Entry::Settings,
+    Entry::HighScores,
+    Entry::Quit,
+];
+
+fn label(entry: Entry, mode: Mode) -> String {
+    match entry {
+        Entry::Play => "Play".to_string(),
+        Entry::Mode => format!("Mode:  {}", mode.name()),
+        Entry::Settings => "Settings".to_string(),
+        Entry::HighScores => "High Scores".to_string(),
+        Entry::Quit => "Quit".to_string(),
+    }
+}
+
+/// Title screen the game boots into. Up and down pick an entry, left and
+/// right change the mode, confirm activates the entry.
+pub struct Title {
+    config: Config,
+    mode: usize,
+    selected: usize,
+    camera: Camera2D,
+}
+
+impl Title {
+    pub fn new(input: &Input, config: Config) -> Self {
+        let mut camera = Camera2D::new(input, 20.);
+        camera.fit_arena(&config.arena(), input.width, input.height);
+        Self {
+            mode: Mode::ALL
+                .iter()
+                .position(|mode| *mode == config.mode)
+                .unwrap_or(0),
+            selected: 0,
+            config,
+            camera,
+        }
+    }
+
+    fn mode(&self) -> Mode {
+        Mode::ALL[self.mode]
+    }
+
+    /// Where the entry at `index` is drawn.
+    fn line(&self, index: usize) -> Vec2 {
+        Vec2::new(-6., 4. - index as f32 * LINE_HEIGHT)
+    }
+
+    fn activate(
+        &mut self,
+        entry: Entry,
+        input: &Input,
+        renderer: &mut graphics::MainRenderer,
+    ) -> NextStage {
+        match entry {
+            Entry::Play => {
+                let config = self.mode().configure(&self.config);
+                NextStage::Replace(Box::new(GameState::new(input, renderer, &config)))
+            }
+            Entry::Mode => {
+                self.mode = (self.mode + 1) % Mode::ALL.len();
+                NextStage::Noop
+            }
+            Entry::Settings => {
+                let menu = SettingsMenu::from_title(input, self.config.clone());
+                NextStage::Replace(Box::new(menu))
+            }
+            Entry::HighScores => {
+                let mut config = self.config.clone();
+                config.mode = self.mode();
+                NextStage::Replace(Box::new(HighScoreTable::new(input, config, self.mode())))
+            }
+            Entry::Quit => NextStage::Quit,
+        }
+    }
+}
+
+impl Stage for Title {
+    fn enter(&mut self, renderer: &mut graphics::MainRenderer) {
+        let mut labels = vec![MARKER.to_string(), "Snek".to_string()];
+        for mode in Mode::ALL.iter() {
+            labels.extend(ENTRIES.iter().map(|entry| label(*entry, *mode)));
+        }
+        labels.sort();
+        labels.dedup();
+        for text in labels {
+            renderer
+                .asset_commands
+                .push(graphics::systems::load_text_command(&Text::new(&text)));
+        }
+    }
+
+    fn update(&mut self, input: &Input, renderer: &mut graphics::MainRenderer) -> NextStage {
+        if input.resized {
+            let Input { width, height, .. } = *input;
+            self.camera.fit_arena(&self.config.arena(), width, height);
+        }
+        if input.triggered(Action::Back) {
+            return NextStage::Quit;
+        }
+        let count = ENTRIES.len();
+        if input.pressed(Direction::Up) {
+            self.selected = (self.selected + count - 1) % count;
+        }
+        if input.pressed(Direction::Down) {
+            self.selected = (self.selected + 1) % count;
+        }
+        if ENTRIES[self.selected] == Entry::Mode {
+            let modes = Mode::ALL.len();
+            if input.pressed(Direction::Left) {
+                self.mode = (self.mode + modes - 1) % modes;
+            }
+            if input.pressed(Direction::Right) {
+                self.mode = (self.mode + 1) % modes;
+            }
+        }
+        let lines = (0..ENTRIES.len()).map(|index| self.line(index));
+        if let Some(index) = stages::tapped_line(input, &self.camera, lines, LINE_HEIGHT) {
+            self.selected = index;
+            return self.activate(ENTRIES[index], input, renderer);
+        }
+        if input.triggered(Action::Confirm) {
+            return self.activate(ENTRIES[self.selected], input, renderer);
+        }
+        NextStage::Noop
+    }
+
+    fn draw(&mut self, renderer: &mut graphics::MainRenderer) {
+        renderer.update_view(&self.camera);
+        let mode = self.mode();
+        let mut lines: Vec<(String, Vec2)> = vec![("Snek".to_string(), Vec2::new(-6., 8.))];
+        for (index, entry) in ENTRIES.iter().enumerate() {
+            lines.push((label(*entry, mode), self.line(index)));
+        }
+        lines.push((
+            MARKER.to_string(),
+            self.line(self.selected) - Vec2::new(2., 0.),
+        ));
+
+        let commands = &mut renderer.main_render_target.commands;
+        for (text, position) in lines {
+            commands.push(graphics::renderer::RenderCommand::DrawFont(
+                graphics::renderer::DrawFont {
+                    text,
+                    font: "KenneyFuture".into(),
+                    position,
+                },
+            ));
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Where the game keeps things between runs.
+use std::path::PathBuf;
+
+use snake::scores::{FileStore, MemoryStore, ScoreStore};
+
+const APP_DIR: &str = "snek";
+const HIGH_SCORES_FILE: &str = "highscores.txt";
+
+/// The per-user data directory for the game, following each platform's
+/// convention. `None` when the environment doesn't say where that is.
+pub fn data_dir() -> Option<PathBuf> {
+    let env = |key: &str| std::env::var_os(key).map(PathBuf::from);
+    let base = if cfg!(target_os = "windows") {
+        env("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env("XDG_DATA_HOME").or_else(|| env("HOME").map(|home| home.join(".local").join("share")))
+    };
+    base.map(|base| base.join(APP_DIR))
+}
+
+/// The high score store for this platform. Builds without a filesystem
+/// don't keep scores yet, they get an empty store every time.
+pub fn high_score_store() -> Box<dyn ScoreStore> {
+    if cfg!(target_arch = "wasm32") {
+        return Box::new(MemoryStore::new());
+    }
+    let path = data_dir()
+        .map(|dir| dir.join(HIGH_SCORES_FILE))
+        .unwrap_or_else(|| PathBuf::from(HIGH_SCORES_FILE));
+    Box::new(FileStore::new(path))
+}
@@ -0,0 +1,12 @@
+use miniquad::date;
+use snake::clock::TimeSource;
+
+/// Wall-clock time as reported by miniquad, which also works under wasm.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DateTime;
+
+impl TimeSource for DateTime {
+    fn now(&self) -> f64 {
+        date::now()
+    }
+}
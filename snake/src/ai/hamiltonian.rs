@@ -0,0 +1,132 @@
+use crate::ai::{Cell, Controller, SnakeView, Survivor, WorldView};
+use crate::arena::Arena;
+use crate::components::Direction;
+
+/// Follows a fixed cycle through every cell of the arena, which a lone snake
+/// can keep doing until the board is full. Needs an even width or height, as
+/// no such cycle exists otherwise, and falls back to `Survivor` whenever the
+/// cycle is blocked or the snake hasn't joined it yet.
+#[derive(Debug, Default)]
+pub struct Hamiltonian {
+    arena: Option<Arena>,
+    /// Direction to leave each cell in, indexed row by row from the bottom left.
+    cycle: Option<Vec<Direction>>,
+}
+
+impl Hamiltonian {
+    fn next(&mut self, arena: &Arena, (x, y): Cell) -> Option<Direction> {
+        if self.arena.as_ref() != Some(arena) {
+            self.arena = Some(*arena);
+            self.cycle = build_cycle(arena.width, arena.height);
+        }
+        let (x, y) = (x - arena.left(), y - arena.bottom());
+        self.cycle
+            .as_ref()?
+            .get((y * arena.width + x) as usize)
+            .copied()
+    }
+}
+
+impl Controller for Hamiltonian {
+    fn name(&self) -> &'static str {
+        "hamiltonian"
+    }
+
+    fn choose(&mut self, view: &WorldView, snake: &SnakeView) -> Option<Direction> {
+        let planned = self.next(&view.arena, snake.head);
+        match planned {
+            Some(direction) if view.moves_for(snake).any(|(d, _)| d == direction) => {
+                Some(direction)
+            }
+            _ => Survivor.choose(view, snake),
+        }
+    }
+}
+
+/// Zigzags along the rows from the bottom, leaving the first column free to
+/// come back down on. Transposes the grid when only the width is even.
+fn build_cycle(width: i32, height: i32) -> Option<Vec<Direction>> {
+    if width < 2 || height < 2 {
+        return None;
+    }
+    if height % 2 == 0 {
+        let mut cycle = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                cycle.push(zigzag(x, y, width, height));
+            }
+        }
+        Some(cycle)
+    } else if width % 2 == 0 {
+        let mut cycle = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                cycle.push(transpose(zigzag(y, x, height, width)));
+            }
+        }
+        Some(cycle)
+    } else {
+        None
+    }
+}
+
+/// Where the cycle goes from `(x, y)` on a grid with an even `height`.
+fn zigzag(x: i32, y: i32, width: i32, height: i32) -> Direction {
+    match (x, y % 2) {
+        (0, _) if y == 0 => Direction::Right,
+        (0, _) => Direction::Down,
+        (x, 0) if x == width - 1 => Direction::Up,
+        (_, 0) => Direction::Right,
+        (1, _) if y == height - 1 => Direction::Left,
+        (1, _) => Direction::Up,
+        _ => Direction::Left,
+    }
+}
+
+fn transpose(direction: Direction) -> Direction {
+    match direction {
+        Direction::Up => Direction::Right,
+        Direction::Right => Direction::Up,
+        Direction::Down => Direction::Left,
+        Direction::Left => Direction::Down,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Follows the cycle from the bottom left corner, checking every step
+    /// stays on the grid and every cell is visited once before it closes.
+    fn check_cycle(width: i32, height: i32) {
+        let cycle = build_cycle(width, height).expect("The grid should have a cycle");
+        assert_eq!(cycle.len(), (width * height) as usize);
+        let mut seen = HashSet::new();
+        let (mut x, mut y) = (0, 0);
+        for _ in 0..width * height {
+            assert!(seen.insert((x, y)), "({}, {}) visited twice", x, y);
+            let step = cycle[(y * width + x) as usize].velocity();
+            x += step.x as i32;
+            y += step.y as i32;
+            assert!((0..width).contains(&x) && (0..height).contains(&y));
+        }
+        assert_eq!((x, y), (0, 0));
+        assert_eq!(seen.len(), (width * height) as usize);
+    }
+
+    #[test]
+    fn cycles_visit_every_cell() {
+        check_cycle(2, 2);
+        check_cycle(4, 6);
+        check_cycle(7, 4);
+        check_cycle(6, 5);
+        check_cycle(48, 30);
+    }
+
+    #[test]
+    fn odd_grids_have_no_cycle() {
+        assert!(build_cycle(5, 5).is_none());
+        assert!(build_cycle(1, 4).is_none());
+    }
+}
@@ -0,0 +1,246 @@
+//! Computer-controlled snakes.
+//!
+//! A `Controller` looks at a read-only `WorldView` of the game and picks the
+//! direction its snake should take next. Its choice is pressed into the same
+//! `Input` human players fill, so AI turns go through `systems::update_input`
+//! and end up in replays like any other turn.
+use std::collections::{HashSet, VecDeque};
+
+use crate::arena::{self, Arena, EdgeRule};
+use crate::components::{self, Direction, Player};
+use crate::food::FoodKind;
+use crate::systems::GameWorld;
+
+mod hamiltonian;
+mod seeker;
+mod survivor;
+
+pub use hamiltonian::Hamiltonian;
+pub use seeker::FoodSeeker;
+pub use survivor::Survivor;
+
+pub type Cell = (i32, i32);
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+];
+
+pub trait Controller: std::fmt::Debug {
+    fn name(&self) -> &'static str;
+
+    /// The direction `snake` should head in, `None` keeps its heading.
+    fn choose(&mut self, view: &WorldView, snake: &SnakeView) -> Option<Direction>;
+}
+
+/// Builds a controller from its config name.
+pub fn controller(name: &str) -> Option<Box<dyn Controller>> {
+    match name {
+        "seeker" => Some(Box::new(FoodSeeker)),
+        "survivor" => Some(Box::new(Survivor)),
+        "hamiltonian" => Some(Box::new(Hamiltonian::default())),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SnakeView {
+    pub player: Player,
+    pub head: Cell,
+    pub heading: Direction,
+    pub length: usize,
+    /// True while the snake still has turns queued up.
+    pub turning: bool,
+}
+
+/// A snapshot of everything a controller may look at.
+#[derive(Debug, Clone)]
+pub struct WorldView {
+    pub arena: Arena,
+    pub food: Vec<Cell>,
+    pub snakes: Vec<SnakeView>,
+    blocked: HashSet<Cell>,
+}
+
+impl WorldView {
+    pub fn new(game_world: &GameWorld) -> Self {
+        let GameWorld { world, arena, .. } = game_world;
+        let mut blocked = HashSet::new();
+        let mut food = Vec::new();
+        for (_, (pos, col, head, body, meal)) in &mut world.query::<(
+            &components::Position,
+            Option<&components::Collision>,
+            Option<&components::Snake>,
+            Option<&components::Body>,
+            Option<&components::Food>,
+        )>() {
+            match col.map(|col| col.kind) {
+                // Poison is neither worth going for nor deadly
+                Some(components::CollsionKind::Food)
+                    if meal.map(|meal| meal.0) == Some(FoodKind::Poison) => {}
+                Some(components::CollsionKind::Food) => food.push(arena::cell(pos.0)),
+                Some(components::CollsionKind::Pickup) => {}
+                Some(_) => {
+                    blocked.insert(arena::cell(pos.0));
+                }
+                None if head.is_some() => {
+                    blocked.insert(arena::cell(pos.0));
+                }
+                None => {}
+            }
+            blocked.extend(body.iter().flat_map(|body| body.segments()));
+        }
+
+        let mut snakes: Vec<SnakeView> = world
+            .query::<(
+                &components::Snake,
+                &Player,
+                &components::Position,
+                &components::HeadDirection,
+                &components::TurnQueue,
+                Option<&components::Body>,
+            )>()
+            .iter()
+            .map(|(_, (_, player, pos, dir, queue, body))| SnakeView {
+                player: *player,
+                head: arena::cell(pos.0),
+                heading: dir.0,
+                length: 1 + body.map_or(0, |body| body.len()),
+                turning: !queue.is_empty(),
+            })
+            .collect();
+        snakes.sort_by_key(|snake| snake.player);
+        food.sort();
+
+        WorldView {
+            arena: *arena,
+            food,
+            snakes,
+            blocked,
+        }
+    }
+
+    pub fn snake(&self, player: Player) -> Option<&SnakeView> {
+        self.snakes.iter().find(|snake| snake.player == player)
+    }
+
+    /// The cell one step from `cell`, or `None` when that is through a wall.
+    pub fn step(&self, cell: Cell, direction: Direction) -> Option<Cell> {
+        let velocity = direction.velocity();
+        let next = (cell.0 + velocity.x as i32, cell.1 + velocity.y as i32);
+        let pos = glam::Vec2::new(next.0 as f32, next.1 as f32);
+        match self.arena.edge {
+            EdgeRule::Wrap => Some(arena::cell(self.arena.wrap(pos))),
+            EdgeRule::Walls if self.arena.contains(pos) => Some(next),
+            EdgeRule::Walls => None,
+        }
+    }
+
+    pub fn is_free(&self, cell: Cell) -> bool {
+        !self.blocked.contains(&cell)
+    }
+
+    /// Moves out of `cell` that don't run straight into something.
+    pub fn safe_moves(&self, cell: Cell) -> impl Iterator<Item = (Direction, Cell)> + '_ {
+        DIRECTIONS.iter().filter_map(move |direction| {
+            let next = self.step(cell, *direction)?;
+            if self.is_free(next) {
+                Some((*direction, next))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Safe moves for `snake`, leaving out the reversal its queue would reject.
+    pub fn moves_for<'a>(
+        &'a self,
+        snake: &'a SnakeView,
+    ) -> impl Iterator<Item = (Direction, Cell)> + 'a {
+        self.safe_moves(snake.head)
+            .filter(move |(direction, _)| *direction != snake.heading.opposite())
+    }
+
+    /// Number of free cells reachable from `start`, counting `start` itself.
+    pub fn reachable(&self, start: Cell) -> usize {
+        let mut seen = HashSet::new();
+        let mut open = VecDeque::new();
+        seen.insert(start);
+        open.push_back(start);
+        while let Some(cell) = open.pop_front() {
+            for (_, next) in self.safe_moves(cell) {
+                if seen.insert(next) {
+                    open.push_back(next);
+                }
+            }
+        }
+        seen.len()
+    }
+
+    /// Breadth first search from the first step `first` towards the nearest
+    /// food. Returns the number of steps, including `first`.
+    pub fn distance_to_food(&self, first: Cell) -> Option<usize> {
+        let food: HashSet<Cell> = self.food.iter().copied().collect();
+        let mut seen = HashSet::new();
+        let mut open = VecDeque::new();
+        seen.insert(first);
+        open.push_back((first, 1));
+        while let Some((cell, distance)) = open.pop_front() {
+            if food.contains(&cell) {
+                return Some(distance);
+            }
+            for (_, next) in self.safe_moves(cell) {
+                if seen.insert(next) {
+                    open.push_back((next, distance + 1));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+impl WorldView {
+    /// A view of `arena` with only `food` and the `blocked` cells on it, for
+    /// trying controllers out on.
+    pub(crate) fn with(arena: Arena, food: &[Cell], blocked: &[Cell]) -> Self {
+        WorldView {
+            arena,
+            food: food.to_vec(),
+            snakes: Vec::new(),
+            blocked: blocked.iter().copied().collect(),
+        }
+    }
+}
+
+/// Lets every AI controlled player pick a turn, and presses it into the
+/// tick's `GameWorld::input`. A snake that still has turns queued is left
+/// alone until it takes them.
+pub fn think_system(game_world: &mut GameWorld) {
+    if game_world
+        .players
+        .iter()
+        .all(|state| state.controller.is_none())
+    {
+        return;
+    }
+    let view = WorldView::new(game_world);
+    let GameWorld { players, input, .. } = game_world;
+    for state in players.iter_mut() {
+        let controller = match &mut state.controller {
+            Some(controller) => controller,
+            _ => continue,
+        };
+        let snake = match view.snake(state.player) {
+            Some(snake) if !snake.turning => snake,
+            _ => continue,
+        };
+        if let Some(direction) = controller.choose(&view, snake) {
+            if direction != snake.heading {
+                input.press_for(state.player, direction);
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::ai::{Controller, SnakeView, WorldView};
+use crate::components::Direction;
+
+/// Heads for the nearest food along the shortest free path, and otherwise
+/// takes whichever move doesn't crash right away.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FoodSeeker;
+
+impl Controller for FoodSeeker {
+    fn name(&self) -> &'static str {
+        "seeker"
+    }
+
+    fn choose(&mut self, view: &WorldView, snake: &SnakeView) -> Option<Direction> {
+        let best = view
+            .moves_for(snake)
+            .filter_map(|(direction, next)| Some((view.distance_to_food(next)?, direction)))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, direction)| direction);
+        best.or_else(|| {
+            let mut moves = view.moves_for(snake).map(|(direction, _)| direction);
+            let first = moves.next()?;
+            Some(
+                std::iter::once(first)
+                    .chain(moves)
+                    .find(|d| *d == snake.heading)
+                    .unwrap_or(first),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::{Arena, EdgeRule};
+    use crate::components::Player;
+
+    fn snake() -> SnakeView {
+        SnakeView {
+            player: Player(0),
+            head: (0, 0),
+            heading: Direction::Up,
+            length: 3,
+            turning: false,
+        }
+    }
+
+    #[test]
+    fn heads_for_the_nearest_food() {
+        let arena = Arena::new(10, 10, EdgeRule::Walls);
+        let view = WorldView::with(arena, &[(3, 0), (0, -5)], &[(0, 0)]);
+        assert_eq!(FoodSeeker.choose(&view, &snake()), Some(Direction::Right));
+
+        let view = WorldView::with(arena, &[(3, 0), (-2, 0)], &[(0, 0)]);
+        assert_eq!(FoodSeeker.choose(&view, &snake()), Some(Direction::Left));
+
+        // Around whatever is in the way
+        let view = WorldView::with(arena, &[(0, 3)], &[(0, 0), (0, 1), (1, 1)]);
+        assert_eq!(FoodSeeker.choose(&view, &snake()), Some(Direction::Left));
+    }
+}
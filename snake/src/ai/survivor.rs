@@ -0,0 +1,84 @@
+use crate::ai::{Controller, SnakeView, WorldView};
+use crate::components::Direction;
+
+/// Only chases food along moves that leave the snake enough room to fit its
+/// whole body afterwards. When no such move exists it takes the one with the
+/// most space left.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Survivor;
+
+impl Controller for Survivor {
+    fn name(&self) -> &'static str {
+        "survivor"
+    }
+
+    fn choose(&mut self, view: &WorldView, snake: &SnakeView) -> Option<Direction> {
+        let options: Vec<(Direction, usize, Option<usize>)> = view
+            .moves_for(snake)
+            .map(|(direction, next)| {
+                let room = view.reachable(next);
+                (direction, room, view.distance_to_food(next))
+            })
+            .collect();
+
+        let roomy = options
+            .iter()
+            .filter(|(_, room, _)| *room >= snake.length)
+            .filter_map(|(direction, _, food)| Some((food.as_ref()?, direction)))
+            .min_by_key(|(distance, _)| **distance)
+            .map(|(_, direction)| *direction);
+        roomy.or_else(|| {
+            options
+                .iter()
+                .max_by_key(|(_, room, _)| *room)
+                .map(|(direction, _, _)| *direction)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::FoodSeeker;
+    use crate::arena::{Arena, EdgeRule};
+    use crate::components::Player;
+
+    fn snake(length: usize) -> SnakeView {
+        SnakeView {
+            player: Player(0),
+            head: (0, 0),
+            heading: Direction::Up,
+            length,
+            turning: false,
+        }
+    }
+
+    #[test]
+    fn takes_the_only_safe_move() {
+        let arena = Arena::new(10, 10, EdgeRule::Walls);
+        let view = WorldView::with(arena, &[(0, 3)], &[(0, 0), (0, 1), (1, 0)]);
+        assert_eq!(Survivor.choose(&view, &snake(3)), Some(Direction::Left));
+    }
+
+    #[test]
+    fn leaves_food_in_a_dead_end() {
+        let arena = Arena::new(10, 10, EdgeRule::Walls);
+        // The food sits in a pocket one cell big, the snake wouldn't fit.
+        // Its head blocks the way out, as it does in a real game
+        let view = WorldView::with(arena, &[(1, 0)], &[(0, 0), (0, 1), (2, 0), (1, 1), (1, -1)]);
+        assert_eq!(Survivor.choose(&view, &snake(5)), Some(Direction::Left));
+        assert_eq!(FoodSeeker.choose(&view, &snake(5)), Some(Direction::Right));
+    }
+
+    #[test]
+    fn keeps_off_the_edge() {
+        // Up against the top wall, turning is all that is left
+        let arena = Arena::new(10, 10, EdgeRule::Walls);
+        let view = WorldView::with(arena, &[], &[(0, 4), (-1, 4)]);
+        let snake = SnakeView {
+            head: (0, 4),
+            ..snake(3)
+        };
+        assert_eq!(Survivor.choose(&view, &snake), Some(Direction::Right));
+    }
+}
@@ -0,0 +1,116 @@
+use core::str::FromStr;
+use glam::Vec2;
+
+use crate::rng::Rng;
+
+/// What happens when a snake reaches the edge of the arena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeRule {
+    /// The edge is solid, driving into it ends the game.
+    Walls,
+    /// Leaving one side enters from the opposite one.
+    Wrap,
+}
+
+impl FromStr for EdgeRule {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "walls" => Ok(EdgeRule::Walls),
+            "wrap" => Ok(EdgeRule::Wrap),
+            _ => Err(()),
+        }
+    }
+}
+
+impl core::fmt::Display for EdgeRule {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EdgeRule::Walls => f.write_str("walls"),
+            EdgeRule::Wrap => f.write_str("wrap"),
+        }
+    }
+}
+
+/// The grid cell a world position falls in.
+pub fn cell(pos: Vec2) -> (i32, i32) {
+    (pos.x.round() as i32, pos.y.round() as i32)
+}
+
+/// The playable grid, centred on the origin. Cells run from `left()` to
+/// `right()` and `bottom()` to `top()`, all inclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arena {
+    pub width: i32,
+    pub height: i32,
+    pub edge: EdgeRule,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Arena::new(48, 30, EdgeRule::Walls)
+    }
+}
+
+impl Arena {
+    pub fn new(width: i32, height: i32, edge: EdgeRule) -> Self {
+        Self {
+            width: width.max(1),
+            height: height.max(1),
+            edge,
+        }
+    }
+
+    pub fn left(&self) -> i32 {
+        -self.width / 2
+    }
+
+    pub fn right(&self) -> i32 {
+        self.left() + self.width - 1
+    }
+
+    pub fn bottom(&self) -> i32 {
+        -self.height / 2
+    }
+
+    pub fn top(&self) -> i32 {
+        self.bottom() + self.height - 1
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        let (x, y) = cell(pos);
+        x >= self.left() && x <= self.right() && y >= self.bottom() && y <= self.top()
+    }
+
+    /// Folds a position that left the arena back in from the opposite side.
+    pub fn wrap(&self, pos: Vec2) -> Vec2 {
+        let x = (pos.x.round() as i32 - self.left()).rem_euclid(self.width) + self.left();
+        let y = (pos.y.round() as i32 - self.bottom()).rem_euclid(self.height) + self.bottom();
+        Vec2::new(x as f32, y as f32)
+    }
+
+    /// Where a head at `pos` moving by `velocity` ends up, or `None` if that
+    /// takes it through a wall.
+    pub fn step(&self, pos: Vec2, velocity: Vec2) -> Option<Vec2> {
+        let next = pos + velocity;
+        match self.edge {
+            EdgeRule::Wrap => Some(self.wrap(next)),
+            EdgeRule::Walls if self.contains(next) => Some(next),
+            EdgeRule::Walls => None,
+        }
+    }
+
+    /// Every cell in the arena, row by row from the bottom left.
+    pub fn cells(&self) -> impl Iterator<Item = Vec2> {
+        let (left, right) = (self.left(), self.right());
+        (self.bottom()..=self.top())
+            .flat_map(move |y| (left..=right).map(move |x| Vec2::new(x as f32, y as f32)))
+    }
+
+    pub fn random_cell(&self, rng: &mut Rng) -> Vec2 {
+        let x = rng.gen_range(self.left(), self.right() + 1);
+        let y = rng.gen_range(self.bottom(), self.top() + 1);
+        Vec2::new(x as f32, y as f32)
+    }
+}
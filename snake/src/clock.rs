@@ -0,0 +1,255 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Seconds per simulation tick when nothing else is asked for.
+pub const DEFAULT_STEP: f64 = 1. / 60.;
+
+/// Longest wall-clock gap a single `GameClock::update` will simulate, so a
+/// backgrounded tab doesn't come back to hundreds of queued ticks.
+const MAX_FRAME_TIME: f64 = 0.25;
+
+/// Where a `GameClock` reads the current time from, in seconds.
+pub trait TimeSource {
+    fn now(&self) -> f64;
+}
+
+/// A time source that only moves when told to. Clones share the same time, so
+/// a test can keep one and hand the other to the clock.
+#[derive(Debug, Clone, Default)]
+pub struct ManualTime {
+    now: Rc<Cell<f64>>,
+}
+
+impl ManualTime {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn advance(&self, seconds: f64) {
+        self.now.set(self.now.get() + seconds);
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> f64 {
+        self.now.get()
+    }
+}
+
+/// Fixed timestep clock. Wall-clock time is scaled and accumulated, and handed
+/// out as whole ticks of `step` seconds, so the same amount of simulated time
+/// always produces the same number of ticks however it is split into frames.
+pub struct GameClock {
+    source: Box<dyn TimeSource>,
+    step: f64,
+    scale: f64,
+    time: f64,
+    ticks: u64,
+    last: Option<f64>,
+    paused: bool,
+}
+
+impl GameClock {
+    pub fn new(source: Box<dyn TimeSource>, step: f64) -> Self {
+        Self {
+            source,
+            step,
+            scale: 1.,
+            time: 0.,
+            ticks: 0,
+            last: None,
+            paused: false,
+        }
+    }
+
+    pub fn manual(step: f64) -> (Self, ManualTime) {
+        let time = ManualTime::new();
+        (Self::new(Box::new(time.clone()), step), time)
+    }
+
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Simulated seconds covered by the ticks handed out so far.
+    pub fn elapsed(&self) -> f64 {
+        self.ticks as f64 * self.step
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale.max(0.);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.last = None;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Reads the time source and returns how many ticks to simulate this frame.
+    pub fn update(&mut self) -> u32 {
+        let now = self.source.now();
+        let elapsed = match self.last {
+            Some(last) => (now - last).clamp(0., MAX_FRAME_TIME),
+            _ => 0.,
+        };
+        self.last = Some(now);
+        if self.paused {
+            return 0;
+        }
+        self.advance(elapsed)
+    }
+
+    /// Adds `elapsed` unscaled seconds and returns how many ticks are now due.
+    pub fn advance(&mut self, elapsed: f64) -> u32 {
+        self.time += elapsed * self.scale;
+        // Nudge by a small epsilon so that float error in `time` can't swallow a tick
+        let due = (self.time / self.step + 1e-6).floor() as u64;
+        let ticks = due.saturating_sub(self.ticks);
+        self.ticks += ticks;
+        ticks as u32
+    }
+}
+
+/// Counts simulation ticks rather than wall-clock time.
+#[derive(Debug, Clone)]
+pub struct Timer {
+    period: u32,
+    elapsed: u32,
+}
+
+impl Timer {
+    pub fn new(duration: f64, step: f64) -> Self {
+        Self {
+            period: ticks_for(duration, step),
+            elapsed: 0,
+        }
+    }
+
+    pub fn set_duration(&mut self, duration: f64, step: f64) {
+        self.period = ticks_for(duration, step);
+    }
+
+    pub fn period(&self) -> u32 {
+        self.period
+    }
+
+    pub fn tick(&mut self) {
+        self.elapsed += 1;
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.period
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = 0;
+    }
+}
+
+/// Whole ticks of `step` seconds covering `duration`, at least one.
+pub fn ticks_for(duration: f64, step: f64) -> u32 {
+    ((duration / step).round() as u32).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `clock` through `frames`, in seconds of wall-clock time each, and
+    /// returns the ticks handed out.
+    fn run(clock: &mut GameClock, time: &ManualTime, frames: &[f64]) -> u64 {
+        frames
+            .iter()
+            .map(|frame| {
+                time.advance(*frame);
+                clock.update() as u64
+            })
+            .sum()
+    }
+
+    #[test]
+    fn same_ticks_however_time_is_split() {
+        let splits: [Vec<f64>; 4] = [
+            vec![1. / 60.; 180],
+            vec![0.1; 30],
+            vec![0.25; 12],
+            [0.013, 0.2, 0.041, 0.09, 0.006].repeat(9),
+        ];
+        for frames in splits.iter() {
+            let (mut clock, time) = GameClock::manual(DEFAULT_STEP);
+            clock.update();
+            let seconds: f64 = frames.iter().sum();
+            let ticks = run(&mut clock, &time, frames);
+            assert_eq!(ticks, (seconds / DEFAULT_STEP + 1e-6).floor() as u64);
+            assert_eq!(ticks, clock.ticks());
+        }
+    }
+
+    #[test]
+    fn advance_matches_whole_seconds() {
+        let (mut clock, _) = GameClock::manual(DEFAULT_STEP);
+        let ticks: u64 = (0..300).map(|_| clock.advance(0.01) as u64).sum();
+        assert_eq!(ticks, 180);
+    }
+
+    #[test]
+    fn long_frames_are_capped() {
+        let (mut clock, time) = GameClock::manual(DEFAULT_STEP);
+        clock.update();
+        assert_eq!(run(&mut clock, &time, &[5.]), 15);
+    }
+
+    #[test]
+    fn scale_speeds_time_up() {
+        let (mut clock, time) = GameClock::manual(DEFAULT_STEP);
+        clock.set_scale(2.);
+        clock.update();
+        assert_eq!(run(&mut clock, &time, &[0.1; 10]), 120);
+        clock.set_scale(0.5);
+        assert_eq!(run(&mut clock, &time, &[0.1; 10]), 30);
+    }
+
+    #[test]
+    fn paused_time_is_not_simulated() {
+        let (mut clock, time) = GameClock::manual(DEFAULT_STEP);
+        clock.update();
+        assert_eq!(run(&mut clock, &time, &[0.1; 5]), 30);
+        clock.pause();
+        assert_eq!(run(&mut clock, &time, &[0.1; 20]), 0);
+        clock.resume();
+        assert_eq!(run(&mut clock, &time, &[0.1; 5]), 30);
+        assert!((clock.elapsed() - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn timer_counts_ticks() {
+        let mut timer = Timer::new(0.25, DEFAULT_STEP);
+        assert_eq!(timer.period(), 15);
+        for _ in 0..14 {
+            timer.tick();
+        }
+        assert!(!timer.finished());
+        timer.tick();
+        assert!(timer.finished());
+        timer.reset();
+        assert!(!timer.finished());
+        timer.set_duration(0., DEFAULT_STEP);
+        assert_eq!(timer.period(), 1);
+    }
+}
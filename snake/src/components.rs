@@ -0,0 +1,470 @@
+use core::str::FromStr;
+use glam::{Mat4, Quat, Vec2, Vec3};
+use std::collections::VecDeque;
+
+use crate::{arena::Arena, components, food::FoodKind, powerup::PowerUp, AssetIdentity};
+
+pub use crate::input::Input;
+
+/// World units kept visible around the arena, the HUD lives in the top one.
+const ARENA_MARGIN: f32 = 5.;
+
+/// Turns a snake can have queued up ahead of its next moves.
+pub const TURN_QUEUE_CAPACITY: usize = 3;
+
+#[derive(Copy, Clone)]
+pub struct Camera2D {
+    pub scale: f32,
+    pub view: Mat4,
+    pub projection: Mat4,
+}
+
+impl Camera2D {
+    pub fn new(input: &components::Input, scale: f32) -> Camera2D {
+        let components::Input { width, height, .. } = input;
+        let aspect = width / height;
+        let projection =
+            Mat4::orthographic_rh_gl(-aspect * scale, aspect * scale, -scale, scale, -1., 1.0);
+        let view = Mat4::from_rotation_translation(Quat::identity(), Vec3::new(0.0, 0., 0.));
+
+        Camera2D {
+            scale,
+            view,
+            projection,
+        }
+    }
+
+    pub fn resize(&mut self, width: f32, height: f32) {
+        let aspect = width / height;
+        #[rustfmt::skip]
+        let projection = Mat4::orthographic_rh_gl(
+                -aspect * self.scale,
+                aspect * self.scale,
+                -self.scale,
+                self.scale,
+                -1.,
+                1.0
+                );
+        self.projection = projection;
+    }
+
+    /// Picks the smallest scale that keeps the whole arena on screen.
+    pub fn fit_arena(&mut self, arena: &Arena, width: f32, height: f32) {
+        if width <= 0. || height <= 0. {
+            return;
+        }
+        let aspect = width / height;
+        let half_width = arena.width as f32 / 2. + ARENA_MARGIN;
+        let half_height = arena.height as f32 / 2. + ARENA_MARGIN;
+        self.scale = half_height.max(half_width / aspect);
+        self.resize(width, height);
+    }
+
+    /// The world position under a point on a `width` by `height` screen,
+    /// measured in pixels from its top left corner.
+    pub fn screen_to_world(&self, screen: Vec2, width: f32, height: f32) -> Vec2 {
+        let ndc = Vec3::new(screen.x / width * 2. - 1., 1. - screen.y / height * 2., 0.);
+        let world = (self.projection * self.view)
+            .inverse()
+            .transform_point3(ndc);
+        Vec2::new(world.x, world.y)
+    }
+}
+pub struct Snake;
+pub struct Seed;
+/// Marks the HUD text showing how fast the snakes move.
+pub struct Speed;
+
+/// Which player a snake belongs to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Player(pub usize);
+
+impl Player {
+    /// The per-player variant of a material, e.g. `Snake1` for player 1.
+    pub fn material(&self, name: &str) -> Material {
+        Material(format!("{}{}", name, self.0).into())
+    }
+}
+
+/// Marks the HUD text showing a player's score.
+pub struct Score(pub Player);
+pub struct Food(pub FoodKind);
+
+/// Despawns the entity, taking it off the grid, once the game reaches `tick`.
+pub struct Expires {
+    pub tick: u64,
+}
+
+/// Scales the move interval by `scale` until the game reaches `tick`. Every
+/// snake moves on the same timer, so one snake's pace sets everyone's.
+#[derive(Debug, Clone, Copy)]
+pub struct Pace {
+    pub scale: f64,
+    pub tick: u64,
+}
+
+/// A wall inside the arena, placed from the level.
+pub struct Obstacle;
+
+impl Obstacle {
+    /// Everything an obstacle entity at `cell` is made of.
+    pub fn bundle(cell: (i32, i32)) -> (Position, Collision, Obstacle, Material, Mesh) {
+        (
+            Position(Vec2::new(cell.0 as f32, cell.1 as f32)),
+            Collision::wall(),
+            Obstacle,
+            Material("Wall".into()),
+            Mesh("Wall".into()),
+        )
+    }
+}
+
+/// Marks the HUD text listing a player's active power-ups.
+pub struct PowerUps(pub Player);
+
+/// A power-up on the board, waiting to be driven over.
+pub struct Pickup(pub PowerUp);
+
+/// Lets the head pass through its own tail until the game reaches `tick`.
+#[derive(Debug, Clone, Copy)]
+pub struct Ghost {
+    pub tick: u64,
+}
+
+/// Slows every snake down until the game reaches `tick`.
+#[derive(Debug, Clone, Copy)]
+pub struct SlowMo {
+    pub tick: u64,
+}
+
+/// Pulls nearby food towards the head until the game reaches `tick`.
+#[derive(Debug, Clone, Copy)]
+pub struct Magnet {
+    pub tick: u64,
+}
+
+/// Absorbs the next fatal collision, unless the game reaches `tick` first.
+#[derive(Debug, Clone, Copy)]
+pub struct Shield {
+    pub tick: u64,
+}
+
+/// The cells behind a snake's head, nearest first, kept as a ring buffer so
+/// moving and growing don't depend on the snake's length.
+pub struct Body {
+    cells: VecDeque<(i32, i32)>,
+    grow: u32,
+    pub material: Material,
+    pub mesh: Mesh,
+}
+
+impl Body {
+    pub fn new(
+        cells: impl IntoIterator<Item = (i32, i32)>,
+        material: Material,
+        mesh: Mesh,
+    ) -> Self {
+        Self {
+            cells: cells.into_iter().collect(),
+            grow: 0,
+            material,
+            mesh,
+        }
+    }
+
+    /// Segments behind the head, not counting the head itself.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Cells from the neck to the tip of the tail.
+    pub fn segments(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.cells.iter().copied()
+    }
+
+    /// Adds `segments` to the body over the next moves.
+    pub fn grow(&mut self, segments: u32) {
+        self.grow += segments;
+    }
+
+    /// Takes up to `segments` off the body, cancelling growth still to come
+    /// before cutting the tail. Returns the cells the tail let go of.
+    pub fn shrink(&mut self, segments: u32) -> Vec<(i32, i32)> {
+        let pending = segments.min(self.grow);
+        self.grow -= pending;
+        (pending..segments)
+            .filter_map(|_| self.cells.pop_back())
+            .collect()
+    }
+
+    /// Follows a head that is about to leave `head`. Returns the cell the tip
+    /// of the tail left, or `None` while the body is still growing.
+    pub fn advance(&mut self, head: (i32, i32)) -> Option<(i32, i32)> {
+        self.cells.push_front(head);
+        if self.grow > 0 {
+            self.grow -= 1;
+            return None;
+        }
+        self.cells.pop_back()
+    }
+}
+
+pub struct Position(pub Vec2);
+pub struct Velocity(pub Vec2);
+
+#[derive(Debug, Copy, Clone)]
+pub enum CollsionKind {
+    Snake,
+    Food,
+    Pickup,
+    /// An obstacle, deadly to drive into.
+    Wall,
+}
+pub struct Collision {
+    pub kind: CollsionKind,
+}
+
+impl Collision {
+    pub fn snake() -> Self {
+        Self {
+            kind: CollsionKind::Snake,
+        }
+    }
+
+    pub fn food() -> Self {
+        Self {
+            kind: CollsionKind::Food,
+        }
+    }
+
+    pub fn pickup() -> Self {
+        Self {
+            kind: CollsionKind::Pickup,
+        }
+    }
+
+    pub fn wall() -> Self {
+        Self {
+            kind: CollsionKind::Wall,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub fn velocity(&self) -> Vec2 {
+        match self {
+            Direction::Up => Vec2::new(0., 1.),
+            Direction::Right => Vec2::new(1., 0.),
+            Direction::Down => Vec2::new(0., -1.),
+            Direction::Left => Vec2::new(-1., 0.),
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+        }
+    }
+}
+
+impl core::fmt::Display for Direction {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            Direction::Up => "Up",
+            Direction::Right => "Right",
+            Direction::Down => "Down",
+            Direction::Left => "Left",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Up" => Ok(Direction::Up),
+            "Right" => Ok(Direction::Right),
+            "Down" => Ok(Direction::Down),
+            "Left" => Ok(Direction::Left),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct HeadDirection(pub Direction);
+
+/// Turns the player asked for that the snake hasn't taken yet. One is used up
+/// per move, so quick presses between moves all land in order.
+#[derive(Debug, Default)]
+pub struct TurnQueue {
+    turns: VecDeque<Direction>,
+}
+
+impl TurnQueue {
+    /// Queues `turn` unless the queue is full, or it is a no-op or a reversal
+    /// of the heading the snake will have once the queued turns are taken.
+    pub fn push(&mut self, turn: Direction, heading: Direction) -> bool {
+        let ahead = self.turns.back().copied().unwrap_or(heading);
+        if self.turns.len() >= TURN_QUEUE_CAPACITY || turn == ahead || turn == ahead.opposite() {
+            return false;
+        }
+        self.turns.push_back(turn);
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<Direction> {
+        self.turns.pop_front()
+    }
+
+    pub fn clear(&mut self) {
+        self.turns.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.turns.is_empty()
+    }
+}
+
+pub struct Text {
+    string: String,
+    font: String,
+    stale: Option<String>,
+}
+
+impl Text {
+    pub fn new(str: &str) -> Text {
+        Text {
+            string: String::from_str(str).expect("Failed to Create Text"),
+            font: "KenneyFuture".to_string(),
+            stale: None,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.string.clone()
+    }
+
+    pub fn font(&self) -> String {
+        self.font.clone()
+    }
+
+    pub fn update_text(&mut self, new_text: String) {
+        if self.stale.is_none() {
+            self.stale = Some(self.string.clone());
+        }
+        self.string = new_text;
+    }
+
+    /// The text this component displayed before its last update, if the
+    /// frontend hasn't picked up the change yet.
+    pub fn take_stale(&mut self) -> Option<String> {
+        self.stale.take()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Material(pub AssetIdentity);
+
+#[derive(Debug, Clone)]
+pub struct Mesh(pub AssetIdentity);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(cells: &[(i32, i32)]) -> Body {
+        Body::new(
+            cells.iter().copied(),
+            Material("Snake".into()),
+            Mesh("Quad".into()),
+        )
+    }
+
+    fn segments(body: &Body) -> Vec<(i32, i32)> {
+        body.segments().collect()
+    }
+
+    #[test]
+    fn advance_follows_the_head() {
+        let mut body = body(&[(0, -1), (0, -2)]);
+        assert_eq!(body.advance((0, 0)), Some((0, -2)));
+        assert_eq!(segments(&body), [(0, 0), (0, -1)]);
+        assert_eq!(body.advance((1, 0)), Some((0, -1)));
+        assert_eq!(segments(&body), [(1, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn growth_holds_on_to_the_tail() {
+        let mut body = body(&[(0, -1)]);
+        body.grow(2);
+        assert_eq!(body.advance((0, 0)), None);
+        assert_eq!(body.advance((0, 1)), None);
+        assert_eq!(body.advance((0, 2)), Some((0, -1)));
+        assert_eq!(segments(&body), [(0, 2), (0, 1), (0, 0)]);
+    }
+
+    #[test]
+    fn shrink_cancels_growth_first() {
+        let mut body = body(&[(0, -1), (0, -2), (0, -3)]);
+        body.grow(1);
+        assert_eq!(body.shrink(2), [(0, -3)]);
+        assert_eq!(body.advance((0, 0)), Some((0, -2)));
+        assert_eq!(body.shrink(5), [(0, -1), (0, 0)]);
+        assert!(body.is_empty());
+        assert_eq!(body.advance((0, 1)), Some((0, 1)));
+    }
+
+    #[test]
+    fn turn_queue_keeps_turns_in_order() {
+        let mut queue = TurnQueue::default();
+        assert!(queue.push(Direction::Left, Direction::Up));
+        assert!(queue.push(Direction::Down, Direction::Up));
+        assert_eq!(queue.pop(), Some(Direction::Left));
+        assert_eq!(queue.pop(), Some(Direction::Down));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn turn_queue_rejects_no_ops_and_reversals() {
+        let mut queue = TurnQueue::default();
+        assert!(!queue.push(Direction::Up, Direction::Up));
+        assert!(!queue.push(Direction::Down, Direction::Up));
+        assert!(queue.push(Direction::Right, Direction::Up));
+        // Judged against the heading after the queued turn, not the current one
+        assert!(!queue.push(Direction::Left, Direction::Up));
+        assert!(!queue.push(Direction::Right, Direction::Up));
+        assert!(queue.push(Direction::Down, Direction::Up));
+    }
+
+    #[test]
+    fn turn_queue_is_capped() {
+        let mut queue = TurnQueue::default();
+        assert_eq!(TURN_QUEUE_CAPACITY, 3);
+        assert!(queue.push(Direction::Left, Direction::Up));
+        assert!(queue.push(Direction::Up, Direction::Up));
+        assert!(queue.push(Direction::Right, Direction::Up));
+        assert!(!queue.push(Direction::Down, Direction::Up));
+        queue.clear();
+        assert!(queue.is_empty());
+    }
+}
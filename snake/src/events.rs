@@ -0,0 +1,228 @@
+//! Typed event channels.
+//!
+//! Every kind of event has its own `Channel`. Systems send into it and read
+//! out of it through a `Reader`, a cursor that remembers which events that
+//! reader has already seen, so any number of systems can read the same
+//! events without clearing them for each other. A channel keeps what was
+//! sent for two calls to `Channel::update`, which `tick_systems` makes once
+//! per tick, so anything reading at least once per tick sees every event.
+#![allow(dead_code)]
+use std::collections::VecDeque;
+
+use crate::components;
+use crate::food::FoodKind;
+use crate::powerup::PowerUp;
+use crate::systems::Outcome;
+
+/// What ended a snake.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeathCause {
+    /// Drove into the edge of the arena or one of the level's walls.
+    Wall,
+    /// Drove into a tail, `owner` is the snake the tail belongs to.
+    Tail { owner: components::Player },
+    /// Met another snake's head in the same cell.
+    HeadOn { other: components::Player },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SnakeEatFood {
+    pub entity: hecs::Entity,
+    pub pos: glam::Vec2,
+    pub player: components::Player,
+    pub kind: FoodKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PickedUp {
+    pub entity: hecs::Entity,
+    pub pos: glam::Vec2,
+    pub player: components::Player,
+    pub power: PowerUp,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Collision {
+    pub target: hecs::Entity,
+    pub source: hecs::Entity,
+    pub pos: glam::Vec2,
+    pub kind: components::CollsionKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SnakeDied {
+    pub player: components::Player,
+    pub cause: DeathCause,
+}
+
+/// The game is over, either lost or won by filling the arena.
+#[derive(Debug, Clone, Copy)]
+pub struct GameEnded(pub Outcome);
+
+/// Where a reader is up to in a channel.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Reader {
+    /// Id of the first event this reader hasn't seen.
+    next: usize,
+}
+
+#[derive(Debug)]
+pub struct Channel<T> {
+    events: VecDeque<T>,
+    /// Id of the oldest event still kept. Ids count up from zero as events
+    /// are sent and are never reused.
+    first: usize,
+    /// How many of the kept events were sent before the last `update`.
+    previous: usize,
+}
+
+impl<T> Default for Channel<T> {
+    fn default() -> Self {
+        Self {
+            events: VecDeque::new(),
+            first: 0,
+            previous: 0,
+        }
+    }
+}
+
+impl<T> Channel<T> {
+    pub fn send(&mut self, event: T) {
+        self.events.push_back(event);
+    }
+
+    /// A reader that skips everything sent so far.
+    pub fn reader(&self) -> Reader {
+        Reader { next: self.end() }
+    }
+
+    /// Events `reader` hasn't seen yet, oldest first. Events dropped before
+    /// the reader got to them are silently missed.
+    pub fn read(&self, reader: &mut Reader) -> impl Iterator<Item = &T> {
+        let skip = reader.next.saturating_sub(self.first);
+        reader.next = self.end();
+        self.events.iter().skip(skip)
+    }
+
+    /// Every event still kept, whoever has read it.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.events.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Drops the events sent before the previous update.
+    pub fn update(&mut self) {
+        self.events.drain(..self.previous);
+        self.first += self.previous;
+        self.previous = self.events.len();
+    }
+
+    /// Drops every event, readers carry on with whatever is sent next.
+    pub fn clear(&mut self) {
+        self.first = self.end();
+        self.events.clear();
+        self.previous = 0;
+    }
+
+    fn end(&self) -> usize {
+        self.first + self.events.len()
+    }
+}
+
+/// A channel for every kind of event the gameplay systems send.
+#[derive(Debug, Default)]
+pub struct Events {
+    pub food_eaten: Channel<SnakeEatFood>,
+    pub pickups: Channel<PickedUp>,
+    pub collisions: Channel<Collision>,
+    pub deaths: Channel<SnakeDied>,
+    pub game_ended: Channel<GameEnded>,
+}
+
+impl Events {
+    pub fn update(&mut self) {
+        self.food_eaten.update();
+        self.pickups.update();
+        self.collisions.update();
+        self.deaths.update();
+        self.game_ended.update();
+    }
+
+    pub fn clear(&mut self) {
+        self.food_eaten.clear();
+        self.pickups.clear();
+        self.collisions.clear();
+        self.deaths.clear();
+        self.game_ended.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(channel: &Channel<u32>, reader: &mut Reader) -> Vec<u32> {
+        channel.read(reader).copied().collect()
+    }
+
+    #[test]
+    fn readers_keep_their_own_place() {
+        let mut channel = Channel::default();
+        let (mut a, mut b) = (channel.reader(), channel.reader());
+        channel.send(1);
+        channel.send(2);
+        assert_eq!(read(&channel, &mut a), [1, 2]);
+        channel.send(3);
+        assert_eq!(read(&channel, &mut a), [3]);
+        assert_eq!(read(&channel, &mut b), [1, 2, 3]);
+        assert!(read(&channel, &mut a).is_empty());
+    }
+
+    #[test]
+    fn new_readers_skip_what_was_sent() {
+        let mut channel = Channel::default();
+        channel.send(1);
+        let mut reader = channel.reader();
+        assert!(read(&channel, &mut reader).is_empty());
+        channel.send(2);
+        assert_eq!(read(&channel, &mut reader), [2]);
+    }
+
+    #[test]
+    fn events_last_two_updates() {
+        let mut channel = Channel::default();
+        let (mut every_tick, mut late) = (channel.reader(), channel.reader());
+        channel.send(1);
+        channel.update();
+        channel.send(2);
+        assert_eq!(read(&channel, &mut every_tick), [1, 2]);
+        channel.update();
+        assert_eq!(channel.iter().copied().collect::<Vec<_>>(), [2]);
+        channel.send(3);
+        assert_eq!(read(&channel, &mut every_tick), [3]);
+        // Read too late to see the first event, but nothing else is missed
+        assert_eq!(read(&channel, &mut late), [2, 3]);
+    }
+
+    #[test]
+    fn clear_leaves_readers_at_the_end() {
+        let mut channel = Channel::default();
+        let mut reader = channel.reader();
+        channel.send(1);
+        channel.update();
+        channel.send(2);
+        channel.clear();
+        assert!(channel.is_empty());
+        assert!(read(&channel, &mut reader).is_empty());
+        channel.send(3);
+        assert_eq!(read(&channel, &mut reader), [3]);
+        assert_eq!(channel.len(), 1);
+    }
+}
@@ -0,0 +1,160 @@
+//! Kinds of food.
+//!
+//! Every food carries its `FoodKind`, and everything that sets one kind apart
+//! lives in the `FoodSpec` the kind hands out: what it looks like, how often
+//! it spawns, how long it stays on the board and what eating it does. The
+//! systems read the kind off the `SnakeEatFood` event and act on the spec.
+use crate::components::Material;
+use crate::rng::Rng;
+use crate::types::{colors, Color};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoodKind {
+    Plain,
+    /// Worth more points.
+    Bonus,
+    /// Worth a lot, but only around for a few seconds.
+    Golden,
+    /// Shrinks the tail instead of growing it.
+    Poison,
+    /// Speeds the snakes up for a while.
+    Fast,
+    /// Slows the snakes down for a while.
+    Slow,
+}
+
+/// What eating a food does, on top of scoring its points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// Grows the snake by the rules' growth.
+    Grow,
+    /// Takes segments off the tip of the tail.
+    Shrink(u32),
+    /// Scales the move interval for `seconds`. Below one moves quicker.
+    Pace { scale: f64, seconds: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FoodSpec {
+    /// Material the food is drawn with.
+    pub material: &'static str,
+    /// Color of the material. Plain food takes the theme's instead.
+    pub color: Color,
+    /// Odds of spawning relative to the other kinds.
+    pub weight: u32,
+    /// Seconds the food stays on the board, `None` for good.
+    pub lifetime: Option<f64>,
+    pub points: i32,
+    pub effect: Effect,
+}
+
+impl FoodKind {
+    pub const ALL: [FoodKind; 6] = [
+        FoodKind::Plain,
+        FoodKind::Bonus,
+        FoodKind::Golden,
+        FoodKind::Poison,
+        FoodKind::Fast,
+        FoodKind::Slow,
+    ];
+
+    pub fn spec(&self) -> FoodSpec {
+        match self {
+            FoodKind::Plain => FoodSpec {
+                material: "Food",
+                color: colors::PURPLE,
+                weight: 20,
+                lifetime: None,
+                points: 1,
+                effect: Effect::Grow,
+            },
+            FoodKind::Bonus => FoodSpec {
+                material: "BonusFood",
+                color: colors::ORANGE,
+                weight: 4,
+                lifetime: None,
+                points: 3,
+                effect: Effect::Grow,
+            },
+            FoodKind::Golden => FoodSpec {
+                material: "GoldenFood",
+                color: colors::GOLD,
+                weight: 2,
+                lifetime: Some(5.),
+                points: 10,
+                effect: Effect::Grow,
+            },
+            FoodKind::Poison => FoodSpec {
+                material: "PoisonFood",
+                color: colors::DARKGREEN,
+                weight: 3,
+                lifetime: Some(10.),
+                points: 0,
+                effect: Effect::Shrink(2),
+            },
+            FoodKind::Fast => FoodSpec {
+                material: "FastFood",
+                color: colors::RED,
+                weight: 2,
+                lifetime: Some(8.),
+                points: 2,
+                effect: Effect::Pace {
+                    scale: 0.6,
+                    seconds: 5.,
+                },
+            },
+            FoodKind::Slow => FoodSpec {
+                material: "SlowFood",
+                color: colors::SKYBLUE,
+                weight: 2,
+                lifetime: Some(8.),
+                points: 1,
+                effect: Effect::Pace {
+                    scale: 1.5,
+                    seconds: 5.,
+                },
+            },
+        }
+    }
+
+    pub fn material(&self) -> Material {
+        Material(self.spec().material.into())
+    }
+
+    /// Draws a kind at random, going by the spawn weights.
+    pub fn pick(rng: &mut Rng) -> FoodKind {
+        let total: u32 = FoodKind::ALL.iter().map(|kind| kind.spec().weight).sum();
+        let mut roll = rng.gen_range(0, total as i32) as u32;
+        for kind in FoodKind::ALL.iter() {
+            let weight = kind.spec().weight;
+            if roll < weight {
+                return *kind;
+            }
+            roll -= weight;
+        }
+        FoodKind::Plain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_follow_the_weights() {
+        let mut rng = Rng::new(11);
+        let draws_per_weight = 1000;
+        let total: u32 = FoodKind::ALL.iter().map(|kind| kind.spec().weight).sum();
+        let mut counts = [0u32; 6];
+        for _ in 0..total * draws_per_weight {
+            let kind = FoodKind::pick(&mut rng);
+            let index = FoodKind::ALL.iter().position(|k| *k == kind).unwrap();
+            counts[index] += 1;
+        }
+        for (kind, count) in FoodKind::ALL.iter().zip(counts.iter()) {
+            let expected = kind.spec().weight * draws_per_weight;
+            let off = count.max(&expected) - count.min(&expected);
+            assert!(off * 10 < expected, "{:?}: {} of {}", kind, count, expected);
+        }
+    }
+}
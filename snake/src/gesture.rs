@@ -0,0 +1,241 @@
+//! Touch and mouse gestures.
+//!
+//! A `GestureRecognizer` follows a single pointer, a finger or the mouse
+//! while its button is held, and works out what it meant: a swipe in one of
+//! the four directions or a tap. Positions are screen pixels with y growing
+//! downwards and times come from a `TimeSource`, so a test can feed strokes
+//! in without a window.
+use glam::Vec2;
+
+use crate::clock::TimeSource;
+use crate::components::Direction;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    /// Pixels a pointer has to travel to count as a swipe.
+    pub swipe_distance: f32,
+    /// Seconds a swipe may take to cover `swipe_distance`. Slower drags start
+    /// measuring again from where they are.
+    pub swipe_time: f64,
+    /// Pixels a pointer may wander and still count as a tap.
+    pub tap_distance: f32,
+    /// Longest press, in seconds, that counts as a tap.
+    pub tap_time: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            swipe_distance: 30.,
+            swipe_time: 0.5,
+            tap_distance: 10.,
+            tap_time: 0.3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    Swipe(Direction),
+    /// A short press that barely moved, at the screen position it ended.
+    Tap(Vec2),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Stroke {
+    /// Where and when the pointer went down.
+    start: Vec2,
+    pressed: f64,
+    /// Where the current swipe is measured from.
+    origin: Vec2,
+    started: f64,
+    /// Set once the stroke swiped, so letting go doesn't also tap.
+    swiped: bool,
+}
+
+pub struct GestureRecognizer {
+    pub thresholds: Thresholds,
+    source: Box<dyn TimeSource>,
+    stroke: Option<Stroke>,
+}
+
+impl GestureRecognizer {
+    pub fn new(source: Box<dyn TimeSource>, thresholds: Thresholds) -> Self {
+        Self {
+            thresholds,
+            source,
+            stroke: None,
+        }
+    }
+
+    /// Whether a pointer is down.
+    pub fn is_tracking(&self) -> bool {
+        self.stroke.is_some()
+    }
+
+    pub fn press(&mut self, position: Vec2) {
+        let now = self.source.now();
+        self.stroke = Some(Stroke {
+            start: position,
+            pressed: now,
+            origin: position,
+            started: now,
+            swiped: false,
+        });
+    }
+
+    /// Follows the pointer to `position`. A swipe is reported as soon as it
+    /// is long enough, and measuring starts over from there, so one drag can
+    /// steer around several corners.
+    pub fn moved(&mut self, position: Vec2) -> Option<Gesture> {
+        let now = self.source.now();
+        let thresholds = self.thresholds;
+        let stroke = self.stroke.as_mut()?;
+        let delta = position - stroke.origin;
+        if now - stroke.started > thresholds.swipe_time {
+            stroke.origin = position;
+            stroke.started = now;
+            return None;
+        }
+        if delta.length() < thresholds.swipe_distance {
+            return None;
+        }
+        stroke.origin = position;
+        stroke.started = now;
+        stroke.swiped = true;
+        Some(Gesture::Swipe(direction(delta)))
+    }
+
+    /// Ends the stroke at `position`, which is either the end of a swipe or a
+    /// tap, or neither when it was too slow or went nowhere in particular.
+    pub fn release(&mut self, position: Vec2) -> Option<Gesture> {
+        let swipe = self.moved(position);
+        let stroke = self.stroke.take()?;
+        if swipe.is_some() {
+            return swipe;
+        }
+        let held = self.source.now() - stroke.pressed;
+        let tapped = !stroke.swiped
+            && held <= self.thresholds.tap_time
+            && (position - stroke.start).length() <= self.thresholds.tap_distance;
+        if tapped {
+            return Some(Gesture::Tap(position));
+        }
+        None
+    }
+
+    /// Forgets the stroke, e.g. when the system takes the touch away.
+    pub fn cancel(&mut self) {
+        self.stroke = None;
+    }
+}
+
+/// The direction `delta` mostly points in, on a screen where y grows
+/// downwards.
+fn direction(delta: Vec2) -> Direction {
+    if delta.x.abs() > delta.y.abs() {
+        if delta.x > 0. {
+            Direction::Right
+        } else {
+            Direction::Left
+        }
+    } else if delta.y > 0. {
+        Direction::Down
+    } else {
+        Direction::Up
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualTime;
+
+    fn recognizer() -> (GestureRecognizer, ManualTime) {
+        let time = ManualTime::new();
+        let recognizer = GestureRecognizer::new(Box::new(time.clone()), Thresholds::default());
+        (recognizer, time)
+    }
+
+    #[test]
+    fn quick_drag_swipes() {
+        let (mut gestures, time) = recognizer();
+        gestures.press(Vec2::new(100., 100.));
+        time.advance(0.05);
+        assert_eq!(gestures.moved(Vec2::new(110., 95.)), None);
+        time.advance(0.05);
+        assert_eq!(
+            gestures.moved(Vec2::new(140., 95.)),
+            Some(Gesture::Swipe(Direction::Right))
+        );
+        time.advance(0.05);
+        assert_eq!(
+            gestures.moved(Vec2::new(140., 60.)),
+            Some(Gesture::Swipe(Direction::Up))
+        );
+        assert!(gestures.is_tracking());
+    }
+
+    #[test]
+    fn slow_drag_starts_over() {
+        let (mut gestures, time) = recognizer();
+        gestures.press(Vec2::new(0., 0.));
+        time.advance(0.6);
+        assert_eq!(gestures.moved(Vec2::new(0., 40.)), None);
+        time.advance(0.1);
+        // Far enough from the press, but measured from the slow move now
+        assert_eq!(gestures.moved(Vec2::new(0., 60.)), None);
+        time.advance(0.1);
+        assert_eq!(
+            gestures.moved(Vec2::new(0., 75.)),
+            Some(Gesture::Swipe(Direction::Down))
+        );
+    }
+
+    #[test]
+    fn short_press_taps() {
+        let (mut gestures, time) = recognizer();
+        gestures.press(Vec2::new(50., 50.));
+        time.advance(0.1);
+        assert_eq!(
+            gestures.release(Vec2::new(53., 48.)),
+            Some(Gesture::Tap(Vec2::new(53., 48.)))
+        );
+        assert!(!gestures.is_tracking());
+    }
+
+    #[test]
+    fn held_press_does_not_tap() {
+        let (mut gestures, time) = recognizer();
+        gestures.press(Vec2::new(50., 50.));
+        time.advance(0.2);
+        assert_eq!(gestures.moved(Vec2::new(52., 50.)), None);
+        time.advance(0.2);
+        assert_eq!(gestures.release(Vec2::new(52., 50.)), None);
+    }
+
+    #[test]
+    fn release_after_swipe_does_not_tap() {
+        let (mut gestures, time) = recognizer();
+        gestures.press(Vec2::new(50., 50.));
+        time.advance(0.05);
+        assert_eq!(
+            gestures.moved(Vec2::new(15., 50.)),
+            Some(Gesture::Swipe(Direction::Left))
+        );
+        time.advance(0.05);
+        assert_eq!(gestures.release(Vec2::new(15., 50.)), None);
+    }
+
+    #[test]
+    fn release_can_finish_a_swipe() {
+        let (mut gestures, time) = recognizer();
+        gestures.press(Vec2::new(50., 50.));
+        time.advance(0.1);
+        assert_eq!(
+            gestures.release(Vec2::new(50., 90.)),
+            Some(Gesture::Swipe(Direction::Down))
+        );
+        assert_eq!(gestures.release(Vec2::new(50., 90.)), None);
+    }
+}
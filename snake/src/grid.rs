@@ -0,0 +1,124 @@
+use crate::arena::Arena;
+
+/// Which entities occupy each cell of the arena. Systems that spawn, move or
+/// despawn anything solid keep it up to date, so lookups by cell don't have
+/// to scan the world. Cells outside the arena are never occupied. A snake sits
+/// in every cell of its body, so one entity can fill many cells, and even the
+/// same cell twice while its head is about to move on.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    arena: Arena,
+    cells: Vec<Vec<hecs::Entity>>,
+}
+
+impl Grid {
+    pub fn new(arena: &Arena) -> Self {
+        Self {
+            arena: *arena,
+            cells: vec![Vec::new(); (arena.width * arena.height) as usize],
+        }
+    }
+
+    pub fn arena(&self) -> &Arena {
+        &self.arena
+    }
+
+    fn index(&self, (x, y): (i32, i32)) -> Option<usize> {
+        let (x, y) = (x - self.arena.left(), y - self.arena.bottom());
+        if x < 0 || y < 0 || x >= self.arena.width || y >= self.arena.height {
+            return None;
+        }
+        Some((y * self.arena.width + x) as usize)
+    }
+
+    /// Everything in `cell`, in the order it got there.
+    pub fn at(&self, cell: (i32, i32)) -> &[hecs::Entity] {
+        match self.index(cell) {
+            Some(index) => &self.cells[index],
+            _ => &[],
+        }
+    }
+
+    pub fn is_free(&self, cell: (i32, i32)) -> bool {
+        self.at(cell).is_empty()
+    }
+
+    pub fn insert(&mut self, entity: hecs::Entity, cell: (i32, i32)) {
+        if let Some(index) = self.index(cell) {
+            self.cells[index].push(entity);
+        }
+    }
+
+    /// Takes one occurrence of `entity` out of `cell`.
+    pub fn remove(&mut self, entity: hecs::Entity, cell: (i32, i32)) {
+        if let Some(index) = self.index(cell) {
+            let occupants = &mut self.cells[index];
+            if let Some(at) = occupants.iter().position(|other| *other == entity) {
+                occupants.remove(at);
+            }
+        }
+    }
+
+    pub fn relocate(&mut self, entity: hecs::Entity, from: (i32, i32), to: (i32, i32)) {
+        if from != to {
+            self.remove(entity, from);
+            self.insert(entity, to);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(Vec::clear);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::EdgeRule;
+
+    fn setup() -> (Grid, hecs::Entity, hecs::Entity) {
+        let mut world = hecs::World::new();
+        let (a, b) = (world.spawn(()), world.spawn(()));
+        (Grid::new(&Arena::new(4, 4, EdgeRule::Walls)), a, b)
+    }
+
+    #[test]
+    fn keeps_occupants_in_order() {
+        let (mut grid, a, b) = setup();
+        assert!(grid.is_free((0, 0)));
+        grid.insert(a, (0, 0));
+        grid.insert(b, (0, 0));
+        grid.insert(a, (0, 0));
+        assert_eq!(grid.at((0, 0)), &[a, b, a]);
+        grid.remove(a, (0, 0));
+        assert_eq!(grid.at((0, 0)), &[b, a]);
+        grid.remove(b, (1, 1));
+        assert_eq!(grid.at((0, 0)), &[b, a]);
+    }
+
+    #[test]
+    fn ignores_cells_outside_the_arena() {
+        let (mut grid, a, _) = setup();
+        let arena = *grid.arena();
+        grid.insert(a, (arena.right() + 1, 0));
+        grid.insert(a, (0, arena.bottom() - 1));
+        assert!(grid.is_free((arena.right() + 1, 0)));
+        assert!(grid.cells.iter().all(Vec::is_empty));
+        grid.insert(a, (arena.left(), arena.top()));
+        assert_eq!(grid.at((arena.left(), arena.top())), &[a]);
+    }
+
+    #[test]
+    fn relocate_and_clear() {
+        let (mut grid, a, b) = setup();
+        grid.insert(a, (0, 0));
+        grid.insert(b, (1, 0));
+        grid.relocate(a, (0, 0), (1, 0));
+        assert!(grid.is_free((0, 0)));
+        assert_eq!(grid.at((1, 0)), &[b, a]);
+        grid.relocate(b, (1, 0), (1, 0));
+        assert_eq!(grid.at((1, 0)), &[b, a]);
+        grid.clear();
+        assert!(grid.is_free((1, 0)));
+    }
+}
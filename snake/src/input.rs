@@ -0,0 +1,155 @@
+//! Input actions.
+//!
+//! Everything that can steer the game, whether keyboard, mouse, touch, a
+//! replay file or an AI controller, turns what it saw into `Action`s and
+//! hands them to an `Input` with `Input::act`. Stages and systems only ever
+//! read the `Input`, so they don't know or care which device an action came
+//! from.
+use core::str::FromStr;
+use glam::Vec2;
+
+use crate::components::{Direction, Player};
+
+/// Most actions a single `Input` keeps, further ones are dropped.
+const MAX_INPUT_ACTIONS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Turn for a player, or for a keyboard layout until the game seats it.
+    /// Menus treat it as moving the selection.
+    Turn(Player, Direction),
+    Pause,
+    Back,
+    Confirm,
+    /// Runs the game faster for as long as it is held.
+    Boost,
+}
+
+/// Written the way config files name actions, e.g. `p1.up` or `pause`.
+impl core::fmt::Display for Action {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Action::Turn(player, direction) => write!(
+                f,
+                "p{}.{}",
+                player.0 + 1,
+                direction.to_string().to_ascii_lowercase()
+            ),
+            Action::Pause => f.write_str("pause"),
+            Action::Back => f.write_str("back"),
+            Action::Confirm => f.write_str("confirm"),
+            Action::Boost => f.write_str("boost"),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pause" => return Ok(Action::Pause),
+            "back" => return Ok(Action::Back),
+            "confirm" => return Ok(Action::Confirm),
+            "boost" => return Ok(Action::Boost),
+            _ => {}
+        }
+        let (player, direction) = s.strip_prefix('p').ok_or(())?.split_once('.').ok_or(())?;
+        let player = usize::from_str(player).map_err(|_| ())?;
+        let direction = match direction {
+            "up" => Direction::Up,
+            "right" => Direction::Right,
+            "down" => Direction::Down,
+            "left" => Direction::Left,
+            _ => return Err(()),
+        };
+        match player {
+            0 => Err(()),
+            _ => Ok(Action::Turn(Player(player - 1), direction)),
+        }
+    }
+}
+
+/// The actions since the last frame, in the order they happened, plus the
+/// window size and where the screen was tapped.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct Input {
+    pub width: f32,
+    pub height: f32,
+    pub resized: bool,
+    /// Whether boost is held down. Unlike actions it lasts until released.
+    pub boosting: bool,
+    /// Screen position of a tap or click, for stages to hit test.
+    pub tap: Option<Vec2>,
+    actions: [Option<Action>; MAX_INPUT_ACTIONS],
+}
+
+impl Input {
+    pub fn reset(&mut self) {
+        *self = Self {
+            width: self.width,
+            height: self.height,
+            boosting: self.boosting,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_direction(direction: Direction) -> Self {
+        let mut input = Input::default();
+        input.press(direction);
+        input
+    }
+
+    /// Registers an action, keeping the order actions arrived in.
+    pub fn act(&mut self, action: Action) {
+        if action == Action::Boost {
+            self.boosting = true;
+        }
+        if let Some(slot) = self.actions.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(action);
+        }
+    }
+
+    /// Registers a directional press for the first player.
+    pub fn press(&mut self, direction: Direction) {
+        self.press_for(Player(0), direction);
+    }
+
+    pub fn press_for(&mut self, player: Player, direction: Direction) {
+        self.act(Action::Turn(player, direction));
+    }
+
+    /// Actions in the order they happened.
+    pub fn actions(&self) -> impl Iterator<Item = Action> + '_ {
+        self.actions.iter().filter_map(|action| *action)
+    }
+
+    pub fn triggered(&self, action: Action) -> bool {
+        self.actions().any(|other| other == action)
+    }
+
+    /// Whether anyone turned towards `direction`, which is how menus move.
+    pub fn pressed(&self, direction: Direction) -> bool {
+        self.turns().any(|(_, turn)| turn == direction)
+    }
+
+    /// Directional presses in the order they happened.
+    pub fn turns(&self) -> impl Iterator<Item = (Player, Direction)> + '_ {
+        self.actions().filter_map(|action| match action {
+            Action::Turn(player, direction) => Some((player, direction)),
+            _ => None,
+        })
+    }
+
+    pub fn direction(&self) -> Option<Direction> {
+        [
+            Direction::Left,
+            Direction::Right,
+            Direction::Down,
+            Direction::Up,
+        ]
+        .iter()
+        .copied()
+        .find(|direction| self.pressed(*direction))
+    }
+}
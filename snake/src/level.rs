@@ -0,0 +1,119 @@
+//! Level layouts.
+//!
+//! A `Level` is the set of cells inside the arena that hold a wall. Level
+//! files list them one `wall x y` at a time, or a straight run of them with
+//! `line x1 y1 x2 y2`, so internal walls and whole mazes can be drawn out
+//! without touching code.
+use core::str::FromStr;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::textfile::{self, FileError};
+
+pub const LEVEL_VERSION: u32 = 1;
+const MAGIC: &str = "snake-level";
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Level {
+    walls: BTreeSet<(i32, i32)>,
+}
+
+impl Level {
+    /// A level without any walls.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.walls.is_empty()
+    }
+
+    /// Wall cells, column by column from the bottom left.
+    pub fn walls(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.walls.iter().copied()
+    }
+
+    pub fn is_wall(&self, cell: (i32, i32)) -> bool {
+        self.walls.contains(&cell)
+    }
+
+    pub fn add_wall(&mut self, cell: (i32, i32)) {
+        self.walls.insert(cell);
+    }
+
+    /// Walls every cell from `from` to `to`, both included. The two have to
+    /// share a row or a column.
+    pub fn add_line(&mut self, from: (i32, i32), to: (i32, i32)) -> bool {
+        if from.0 != to.0 && from.1 != to.1 {
+            return false;
+        }
+        let (dx, dy) = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+        let mut cell = from;
+        self.add_wall(cell);
+        while cell != to {
+            cell = (cell.0 + dx, cell.1 + dy);
+            self.add_wall(cell);
+        }
+        true
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut out = String::new();
+        textfile::write_header(&mut out, MAGIC, LEVEL_VERSION);
+        write_walls(&mut out, self);
+        out
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, FileError> {
+        let mut level = Level::new();
+        for (number, line) in textfile::lines(contents, MAGIC, LEVEL_VERSION)? {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bad_line = || FileError::BadLine(number);
+            let (key, value) = line.split_once(' ').ok_or_else(bad_line)?;
+            if !parse_wall(key, value, &mut level) {
+                return Err(bad_line());
+            }
+        }
+        Ok(level)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), FileError> {
+        textfile::save(path, &self.serialize())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FileError> {
+        Level::parse(&textfile::load(path)?)
+    }
+}
+
+/// Writes a `wall` line for every wall of `level`, as level files and replays
+/// keep them.
+pub(crate) fn write_walls(out: &mut String, level: &Level) {
+    for (x, y) in level.walls() {
+        let _ = writeln!(out, "wall {} {}", x, y);
+    }
+}
+
+/// Adds the walls on a `wall` or `line` line to `level`. Returns false when
+/// `key` is neither or `value` doesn't parse.
+pub(crate) fn parse_wall(key: &str, value: &str, level: &mut Level) -> bool {
+    let numbers: Vec<i32> = match value
+        .split_whitespace()
+        .map(i32::from_str)
+        .collect::<Result<_, _>>()
+    {
+        Ok(numbers) => numbers,
+        _ => return false,
+    };
+    match (key, numbers.as_slice()) {
+        ("wall", [x, y]) => {
+            level.add_wall((*x, *y));
+            true
+        }
+        ("line", [x1, y1, x2, y2]) => level.add_line((*x1, *y1), (*x2, *y2)),
+        _ => false,
+    }
+}
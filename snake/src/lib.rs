@@ -0,0 +1,28 @@
+//! Headless Snek simulation.
+//!
+//! Holds the `GameWorld`, its components and the gameplay systems. Nothing in
+//! here knows about miniquad or the renderer, so bots, tests and tools can
+//! drive a game tick by tick without opening a window.
+
+pub mod ai;
+pub mod arena;
+pub mod clock;
+pub mod components;
+pub mod events;
+pub mod food;
+pub mod gesture;
+pub mod grid;
+pub mod input;
+pub mod level;
+pub mod powerup;
+pub mod replay;
+pub mod rng;
+pub mod rules;
+pub mod schedule;
+pub mod scores;
+pub mod systems;
+pub mod textfile;
+pub mod types;
+
+pub use systems::GameWorld;
+pub use types::{AssetIdentity, Color};
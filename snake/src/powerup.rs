@@ -0,0 +1,117 @@
+//! Power-ups.
+//!
+//! Now and then a pickup spawns alongside the food. The snake that drives
+//! over it gets a status component for a few seconds, which the systems
+//! check for. Durations are counted in simulation ticks, so they stand still
+//! whenever the game clock does, e.g. while the game is paused.
+use crate::components::{Ghost, Magnet, Material, Pace, Shield, SlowMo};
+use crate::types::{colors, Color};
+
+/// One food spawn in this many brings a pickup along.
+pub const SPAWN_ODDS: i32 = 8;
+
+/// Seconds a pickup waits on the board to be collected.
+pub const PICKUP_LIFETIME: f64 = 8.;
+
+/// How much longer the move interval gets under slow-mo.
+pub const SLOW_MO_SCALE: f64 = 1.6;
+
+/// Cells a magnet reaches out to pull food in from, along either axis.
+pub const MAGNET_RANGE: i32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerUp {
+    /// The head passes through its own tail.
+    Ghost,
+    /// Every snake moves slower.
+    SlowMo,
+    /// Nearby food creeps towards the head.
+    Magnet,
+    /// The next fatal collision is shrugged off. A snake that ran into the
+    /// edge of the arena stands still for that move.
+    Shield,
+}
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 4] = [
+        PowerUp::Ghost,
+        PowerUp::SlowMo,
+        PowerUp::Magnet,
+        PowerUp::Shield,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerUp::Ghost => "Ghost",
+            PowerUp::SlowMo => "Slow-mo",
+            PowerUp::Magnet => "Magnet",
+            PowerUp::Shield => "Shield",
+        }
+    }
+
+    /// Seconds the status lasts once picked up.
+    pub fn duration(&self) -> f64 {
+        match self {
+            PowerUp::Ghost => 6.,
+            PowerUp::SlowMo => 5.,
+            PowerUp::Magnet => 8.,
+            PowerUp::Shield => 10.,
+        }
+    }
+
+    pub fn material(&self) -> Material {
+        Material(
+            match self {
+                PowerUp::Ghost => "GhostPickup",
+                PowerUp::SlowMo => "SlowMoPickup",
+                PowerUp::Magnet => "MagnetPickup",
+                PowerUp::Shield => "ShieldPickup",
+            }
+            .into(),
+        )
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PowerUp::Ghost => colors::LIGHTGRAY,
+            PowerUp::SlowMo => colors::BLUE,
+            PowerUp::Magnet => colors::MAGENTA,
+            PowerUp::Shield => colors::YELLOW,
+        }
+    }
+}
+
+/// A time-limited component, gone once the game reaches `until`.
+pub trait Status: hecs::Component {
+    fn until(&self) -> u64;
+}
+
+impl Status for Pace {
+    fn until(&self) -> u64 {
+        self.tick
+    }
+}
+
+impl Status for Ghost {
+    fn until(&self) -> u64 {
+        self.tick
+    }
+}
+
+impl Status for SlowMo {
+    fn until(&self) -> u64 {
+        self.tick
+    }
+}
+
+impl Status for Magnet {
+    fn until(&self) -> u64 {
+        self.tick
+    }
+}
+
+impl Status for Shield {
+    fn until(&self) -> u64 {
+        self.tick
+    }
+}
@@ -0,0 +1,296 @@
+//! Input recording and playback.
+//!
+//! A replay is the seed, rules and level a game started from plus every turn fed to the
+//! snakes, tagged with the simulation tick and player it was fed for. Ticks without input aren't
+//! stored. Feeding those turns back through `systems::update_input` on the
+//! same ticks reproduces the game exactly.
+use core::str::FromStr;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::arena::Arena;
+use crate::components::{self, Direction, Player};
+use crate::level::{self, Level};
+use crate::rules::{self, RuleSet};
+use crate::textfile::{self, FileError};
+
+pub const REPLAY_VERSION: u32 = 10;
+const MAGIC: &str = "snake-replay";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub rules: RuleSet,
+    pub level: Level,
+    pub players: usize,
+    /// Number of ticks the recorded game lasted.
+    pub ticks: u64,
+    /// Turns as `(tick, player, direction)`, in the order they were fed.
+    pub turns: Vec<(u64, Player, Direction)>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, rules: RuleSet, level: Level, players: usize) -> Self {
+        Self {
+            seed,
+            rules,
+            level,
+            players,
+            ticks: 0,
+            turns: Vec::new(),
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut out = String::with_capacity(32 + self.turns.len() * 12);
+        textfile::write_header(&mut out, MAGIC, REPLAY_VERSION);
+        let _ = writeln!(out, "seed {}", self.seed);
+        let Arena {
+            width,
+            height,
+            edge,
+        } = self.rules.arena;
+        let _ = writeln!(out, "arena {} {} {}", width, height, edge);
+        let RuleSet {
+            move_interval,
+            food_interval,
+            food_cap,
+            growth,
+            start_length,
+            ..
+        } = self.rules;
+        let _ = writeln!(
+            out,
+            "rules {} {} {} {} {}",
+            move_interval, food_interval, food_cap, growth, start_length
+        );
+        rules::write_speed_curve(&mut out, &self.rules.speed);
+        level::write_walls(&mut out, &self.level);
+        let _ = writeln!(out, "players {}", self.players);
+        let _ = writeln!(out, "ticks {}", self.ticks);
+        for (tick, player, direction) in self.turns.iter() {
+            let _ = writeln!(out, "{} {} {}", tick, player.0, direction);
+        }
+        out
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, FileError> {
+        let mut replay = Replay::new(0, RuleSet::default(), Level::new(), 1);
+        for (number, line) in textfile::lines(contents, MAGIC, REPLAY_VERSION)? {
+            if line.is_empty() {
+                continue;
+            }
+            let bad_line = || FileError::BadLine(number);
+            let (key, value) = line.split_once(' ').ok_or_else(bad_line)?;
+            match key {
+                "seed" => replay.seed = u64::from_str(value).map_err(|_| bad_line())?,
+                "ticks" => replay.ticks = u64::from_str(value).map_err(|_| bad_line())?,
+                "arena" => replay.rules.arena = rules::parse_arena(value).ok_or_else(bad_line)?,
+                "rules" => replay.rules = parse_rules(value, replay.rules).ok_or_else(bad_line)?,
+                "players" => replay.players = usize::from_str(value).map_err(|_| bad_line())?,
+                "speed_curve" | "speed_by" | "fastest" => {
+                    if !rules::parse_speed_curve(key, value, &mut replay.rules.speed) {
+                        return Err(bad_line());
+                    }
+                }
+                "wall" | "line" => {
+                    if !level::parse_wall(key, value, &mut replay.level) {
+                        return Err(bad_line());
+                    }
+                }
+                tick => {
+                    let tick = u64::from_str(tick).map_err(|_| bad_line())?;
+                    let (player, direction) = value.split_once(' ').ok_or_else(bad_line)?;
+                    let player = usize::from_str(player).map_err(|_| bad_line())?;
+                    let direction = Direction::from_str(direction).map_err(|_| bad_line())?;
+                    replay.turns.push((tick, Player(player), direction));
+                }
+            }
+        }
+        Ok(replay)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), FileError> {
+        textfile::save(path, &self.serialize())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FileError> {
+        Replay::parse(&textfile::load(path)?)
+    }
+}
+
+/// The rules on a `rules` line, keeping the arena and speed curve from
+/// `rules`.
+fn parse_rules(value: &str, rules: RuleSet) -> Option<RuleSet> {
+    let mut parts = value.split_whitespace();
+    Some(RuleSet {
+        move_interval: f64::from_str(parts.next()?).ok()?,
+        food_interval: f64::from_str(parts.next()?).ok()?,
+        food_cap: usize::from_str(parts.next()?).ok()?,
+        growth: u32::from_str(parts.next()?).ok()?,
+        start_length: usize::from_str(parts.next()?).ok()?,
+        arena: rules.arena,
+        speed: rules.speed,
+    })
+}
+
+/// Builds a `Replay` one simulation tick at a time.
+#[derive(Debug)]
+pub struct Recorder {
+    replay: Replay,
+}
+
+impl Recorder {
+    pub fn new(seed: u64, rules: RuleSet, level: Level, players: usize) -> Self {
+        Self {
+            replay: Replay::new(seed, rules, level, players),
+        }
+    }
+
+    /// Records the input fed to the simulation ahead of the next tick.
+    pub fn record(&mut self, input: &components::Input) {
+        let tick = self.replay.ticks;
+        self.replay.turns.extend(
+            input
+                .turns()
+                .map(|(player, direction)| (tick, player, direction)),
+        );
+        self.replay.ticks += 1;
+    }
+
+    /// The game recorded so far.
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+}
+
+/// Hands a recorded game back out as one `Input` per simulation tick.
+#[derive(Debug)]
+pub struct Playback {
+    replay: Replay,
+    tick: u64,
+    cursor: usize,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            tick: 0,
+            cursor: 0,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.replay.seed
+    }
+
+    pub fn rules(&self) -> RuleSet {
+        self.replay.rules
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.replay.level
+    }
+
+    pub fn players(&self) -> usize {
+        self.replay.players
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= self.replay.ticks
+    }
+
+    /// The input for the next tick, or `None` once the recording has run out.
+    pub fn next_input(&mut self) -> Option<components::Input> {
+        if self.finished() {
+            return None;
+        }
+        let mut input = components::Input::default();
+        while let Some((tick, player, direction)) = self.replay.turns.get(self.cursor) {
+            if *tick > self.tick {
+                break;
+            }
+            input.press_for(*player, *direction);
+            self.cursor += 1;
+        }
+        self.tick += 1;
+        Some(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::EdgeRule;
+    use crate::rules::Preset;
+
+    fn replay() -> Replay {
+        let mut rules = Preset::Speed.rules();
+        rules.arena = Arena::new(20, 12, EdgeRule::Wrap);
+        let mut level = Level::new();
+        level.add_line((-3, 2), (3, 2));
+        level.add_wall((5, -4));
+        let mut recorder = Recorder::new(1234, rules, level, 2);
+        let mut input = components::Input::default();
+        recorder.record(&input);
+        input.press_for(Player(1), Direction::Left);
+        input.press_for(Player(0), Direction::Right);
+        recorder.record(&input);
+        recorder.record(&components::Input::default());
+        recorder.record(&components::Input::from_direction(Direction::Down));
+        recorder.finish()
+    }
+
+    #[test]
+    fn recorder_tags_turns_with_ticks() {
+        let replay = replay();
+        assert_eq!(replay.ticks, 4);
+        assert_eq!(
+            replay.turns,
+            [
+                (1, Player(1), Direction::Left),
+                (1, Player(0), Direction::Right),
+                (3, Player(0), Direction::Down),
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let replay = replay();
+        assert_eq!(Replay::parse(&replay.serialize()).unwrap(), replay);
+    }
+
+    #[test]
+    fn playback_hands_out_every_tick() {
+        let replay = replay();
+        let mut playback = Playback::new(replay.clone());
+        let inputs: Vec<Vec<(Player, Direction)>> =
+            std::iter::from_fn(|| playback.next_input().map(|input| input.turns().collect()))
+                .collect();
+        assert_eq!(inputs.len() as u64, replay.ticks);
+        assert!(inputs[0].is_empty() && inputs[2].is_empty());
+        assert_eq!(
+            inputs[1],
+            [(Player(1), Direction::Left), (Player(0), Direction::Right)]
+        );
+        assert_eq!(inputs[3], [(Player(0), Direction::Down)]);
+        assert!(playback.finished());
+    }
+
+    #[test]
+    fn parse_rejects_other_versions() {
+        let contents = replay()
+            .serialize()
+            .replacen(&REPLAY_VERSION.to_string(), "1", 1);
+        assert!(matches!(
+            Replay::parse(&contents),
+            Err(FileError::UnsupportedVersion(1))
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+/// Small seedable generator (SplitMix64). Every `GameWorld` owns one so a seed
+/// always produces the same game, independent of any other game running.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    seed: u64,
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// The seed this generator was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in `low..high`, or `low` when the range is empty.
+    pub fn gen_range(&mut self, low: i32, high: i32) -> i32 {
+        if high <= low {
+            return low;
+        }
+        let span = (high as i64 - low as i64) as u64;
+        (low as i64 + (self.next_u64() % span) as i64) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draws(seed: u64) -> Vec<u64> {
+        let mut rng = Rng::new(seed);
+        (0..16).map(|_| rng.next_u64()).collect()
+    }
+
+    #[test]
+    fn a_seed_repeats_its_sequence() {
+        assert_eq!(draws(42), draws(42));
+        assert_eq!(Rng::new(42).seed(), 42);
+    }
+
+    #[test]
+    fn seeds_differ() {
+        assert_ne!(draws(42), draws(43));
+        assert_ne!(draws(0), draws(1));
+    }
+
+    #[test]
+    fn ranges_stay_in_bounds() {
+        let mut rng = Rng::new(7);
+        let mut seen = [false; 5];
+        for _ in 0..1000 {
+            let value = rng.gen_range(-2, 3);
+            assert!((-2..3).contains(&value));
+            seen[(value + 2) as usize] = true;
+        }
+        assert_eq!(seen, [true; 5]);
+
+        // A single value, an empty range and the ends of `i32`
+        assert_eq!(rng.gen_range(4, 5), 4);
+        assert_eq!(rng.gen_range(4, 4), 4);
+        assert_eq!(rng.gen_range(4, -4), 4);
+        for _ in 0..100 {
+            let value = rng.gen_range(i32::MIN, i32::MAX);
+            assert!(value < i32::MAX);
+            assert_eq!(rng.gen_range(i32::MAX - 1, i32::MAX), i32::MAX - 1);
+        }
+    }
+}
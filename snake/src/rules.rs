@@ -0,0 +1,359 @@
+//! Gameplay rules.
+//!
+//! How fast the snakes move, how much food there is and how much it grows
+//! them, how long a snake starts out and the arena it plays in all live in a
+//! `RuleSet`, which the systems read from the `GameWorld` instead of keeping
+//! their own numbers. A `SpeedCurve` shortens the move interval as the game
+//! goes on. The named `Preset`s cover the usual games, rule files start from
+//! one of them and change what they list.
+use core::str::FromStr;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::arena::{Arena, EdgeRule};
+use crate::textfile::{self, FileError};
+
+pub const RULES_VERSION: u32 = 1;
+const MAGIC: &str = "snake-rules";
+
+/// What a `SpeedCurve` counts progress in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+    /// The best score of any player.
+    Score,
+    /// The length of the longest snake, head included.
+    Length,
+}
+
+impl FromStr for Measure {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "score" => Ok(Measure::Score),
+            "length" => Ok(Measure::Length),
+            _ => Err(()),
+        }
+    }
+}
+
+impl core::fmt::Display for Measure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Measure::Score => f.write_str("score"),
+            Measure::Length => f.write_str("length"),
+        }
+    }
+}
+
+/// How the move interval shrinks with progress.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    /// The move interval never changes.
+    Flat,
+    /// Takes `per_point` seconds off for every point of progress.
+    Linear { per_point: f64 },
+    /// Takes `step` seconds off every `every` points of progress.
+    Stepped { every: u32, step: f64 },
+}
+
+/// Written the way rule files give curves, e.g. `linear 0.005` or
+/// `stepped 5 0.02`.
+impl core::fmt::Display for Curve {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Curve::Flat => f.write_str("flat"),
+            Curve::Linear { per_point } => write!(f, "linear {}", per_point),
+            Curve::Stepped { every, step } => write!(f, "stepped {} {}", every, step),
+        }
+    }
+}
+
+impl FromStr for Curve {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let curve = match parts.next() {
+            Some("flat") => Curve::Flat,
+            Some("linear") => Curve::Linear {
+                per_point: seconds(parts.next().ok_or(())?).ok_or(())?,
+            },
+            Some("stepped") => Curve::Stepped {
+                every: u32::from_str(parts.next().ok_or(())?)
+                    .map_err(|_| ())?
+                    .max(1),
+                step: seconds(parts.next().ok_or(())?).ok_or(())?,
+            },
+            _ => return Err(()),
+        };
+        match parts.next() {
+            Some(_) => Err(()),
+            _ => Ok(curve),
+        }
+    }
+}
+
+/// The difficulty curve: how much quicker the snakes move as the game goes
+/// on, down to a cap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedCurve {
+    pub curve: Curve,
+    pub measure: Measure,
+    /// Shortest move interval the curve goes down to, in seconds.
+    pub fastest: f64,
+}
+
+impl Default for SpeedCurve {
+    fn default() -> Self {
+        Self {
+            curve: Curve::Flat,
+            measure: Measure::Score,
+            fastest: 0.05,
+        }
+    }
+}
+
+impl SpeedCurve {
+    /// The move interval after `progress` points, starting from `base`. Never
+    /// goes below `fastest`, nor above `base`.
+    pub fn interval(&self, base: f64, progress: u32) -> f64 {
+        let shortened = match self.curve {
+            Curve::Flat => 0.,
+            Curve::Linear { per_point } => per_point * progress as f64,
+            Curve::Stepped { every, step } => step * (progress / every.max(1)) as f64,
+        };
+        (base - shortened).max(self.fastest.min(base))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// Picks up speed with every point, up to a cap.
+    Classic,
+    /// Quicker snakes that start longer and grow faster.
+    Speed,
+    /// Slow, plenty of food and no walls to die on.
+    Zen,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Classic, Preset::Speed, Preset::Zen];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Classic => "Classic",
+            Preset::Speed => "Speed",
+            Preset::Zen => "Zen",
+        }
+    }
+
+    pub fn rules(&self) -> RuleSet {
+        match self {
+            Preset::Classic => RuleSet {
+                move_interval: 0.25,
+                food_interval: 1.5,
+                food_cap: 10,
+                growth: 1,
+                start_length: 2,
+                arena: Arena::default(),
+                speed: SpeedCurve {
+                    curve: Curve::Linear { per_point: 0.005 },
+                    measure: Measure::Score,
+                    fastest: 0.1,
+                },
+            },
+            Preset::Speed => RuleSet {
+                move_interval: 0.12,
+                food_interval: 1.,
+                food_cap: 6,
+                growth: 2,
+                start_length: 4,
+                arena: Arena::default(),
+                speed: SpeedCurve {
+                    curve: Curve::Linear { per_point: 0.004 },
+                    measure: Measure::Score,
+                    fastest: 0.05,
+                },
+            },
+            Preset::Zen => RuleSet {
+                move_interval: 0.3,
+                food_interval: 1.,
+                food_cap: 20,
+                growth: 1,
+                start_length: 2,
+                arena: Arena::new(48, 30, EdgeRule::Wrap),
+                speed: SpeedCurve::default(),
+            },
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Preset::ALL
+            .iter()
+            .find(|preset| preset.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuleSet {
+    /// Seconds between two steps of the snakes.
+    pub move_interval: f64,
+    /// Seconds between two food spawns.
+    pub food_interval: f64,
+    /// Most food on the board at once.
+    pub food_cap: usize,
+    /// Segments a snake grows by for every food it eats.
+    pub growth: u32,
+    /// Cells a new snake covers, head included.
+    pub start_length: usize,
+    pub arena: Arena,
+    /// Takes `move_interval` down as the game goes on.
+    pub speed: SpeedCurve,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Preset::Classic.rules()
+    }
+}
+
+impl RuleSet {
+    pub fn serialize(&self) -> String {
+        let mut out = String::new();
+        textfile::write_header(&mut out, MAGIC, RULES_VERSION);
+        let _ = writeln!(out, "move_interval {}", self.move_interval);
+        let _ = writeln!(out, "food_interval {}", self.food_interval);
+        let _ = writeln!(out, "food_cap {}", self.food_cap);
+        let _ = writeln!(out, "growth {}", self.growth);
+        let _ = writeln!(out, "start_length {}", self.start_length);
+        let Arena {
+            width,
+            height,
+            edge,
+        } = self.arena;
+        let _ = writeln!(out, "arena {} {} {}", width, height, edge);
+        write_speed_curve(&mut out, &self.speed);
+        out
+    }
+
+    /// Reads a rule file. Rules start out as Classic, a `preset` line swaps
+    /// in another preset and the lines after it change single rules.
+    pub fn parse(contents: &str) -> Result<Self, FileError> {
+        let mut rules = RuleSet::default();
+        for (number, line) in textfile::lines(contents, MAGIC, RULES_VERSION)? {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let bad_line = || FileError::BadLine(number);
+            let (key, value) = line.split_once(' ').ok_or_else(bad_line)?;
+            let value = value.trim();
+            match key {
+                "preset" => rules = Preset::from_str(value).map_err(|_| bad_line())?.rules(),
+                "move_interval" => {
+                    rules.move_interval = interval(value).ok_or_else(bad_line)?;
+                }
+                "food_interval" => {
+                    rules.food_interval = interval(value).ok_or_else(bad_line)?;
+                }
+                "food_cap" => rules.food_cap = usize::from_str(value).map_err(|_| bad_line())?,
+                "growth" => rules.growth = u32::from_str(value).map_err(|_| bad_line())?,
+                "start_length" => {
+                    let length = usize::from_str(value).map_err(|_| bad_line())?;
+                    rules.start_length = length.max(1);
+                }
+                "arena" => rules.arena = parse_arena(value).ok_or_else(bad_line)?,
+                key => {
+                    if !parse_speed_curve(key, value, &mut rules.speed) {
+                        return Err(bad_line());
+                    }
+                }
+            }
+        }
+        Ok(rules)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), FileError> {
+        textfile::save(path, &self.serialize())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FileError> {
+        RuleSet::parse(&textfile::load(path)?)
+    }
+}
+
+/// A positive number of seconds.
+fn interval(value: &str) -> Option<f64> {
+    f64::from_str(value).ok().filter(|seconds| *seconds > 0.)
+}
+
+/// A number of seconds that isn't negative.
+fn seconds(value: &str) -> Option<f64> {
+    f64::from_str(value).ok().filter(|seconds| *seconds >= 0.)
+}
+
+/// Writes the `speed_curve`, `speed_by` and `fastest` lines rule files and
+/// replays keep a `SpeedCurve` in.
+pub(crate) fn write_speed_curve(out: &mut String, speed: &SpeedCurve) {
+    let _ = writeln!(out, "speed_curve {}", speed.curve);
+    let _ = writeln!(out, "speed_by {}", speed.measure);
+    let _ = writeln!(out, "fastest {}", speed.fastest);
+}
+
+/// Applies a `write_speed_curve` line to `speed`. Returns false when `key`
+/// isn't one of them or `value` doesn't parse.
+pub(crate) fn parse_speed_curve(key: &str, value: &str, speed: &mut SpeedCurve) -> bool {
+    match key {
+        "speed_curve" => match Curve::from_str(value) {
+            Ok(curve) => speed.curve = curve,
+            _ => return false,
+        },
+        "speed_by" => match Measure::from_str(value) {
+            Ok(measure) => speed.measure = measure,
+            _ => return false,
+        },
+        "fastest" => match interval(value) {
+            Some(fastest) => speed.fastest = fastest,
+            _ => return false,
+        },
+        _ => return false,
+    }
+    true
+}
+
+pub(crate) fn parse_arena(value: &str) -> Option<Arena> {
+    let mut parts = value.split_whitespace();
+    let width = i32::from_str(parts.next()?).ok()?;
+    let height = i32::from_str(parts.next()?).ok()?;
+    let edge = EdgeRule::from_str(parts.next()?).ok()?;
+    Some(Arena::new(width, height, edge))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_speeds_up_to_a_cap() {
+        let rules = Preset::Classic.rules();
+        let interval = |score| rules.speed.interval(rules.move_interval, score);
+        assert_eq!(interval(0), rules.move_interval);
+        assert!(interval(10) < interval(5));
+        assert_eq!(interval(1000), rules.speed.fastest);
+        assert!(rules.speed.fastest < rules.move_interval);
+    }
+
+    #[test]
+    fn round_trip() {
+        for preset in Preset::ALL.iter() {
+            let rules = preset.rules();
+            assert_eq!(RuleSet::parse(&rules.serialize()).unwrap(), rules);
+        }
+    }
+}
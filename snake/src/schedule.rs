@@ -0,0 +1,377 @@
+//! Declarative system ordering.
+//!
+//! Systems are registered by name into one of the `Phase`s, which run in the
+//! order they are declared in. Inside a phase a system can ask to run before
+//! or after others by name; anything left unconstrained keeps the order it
+//! was added in. A constraint on a system in another phase has to agree with
+//! the order of the phases. Game modes add, replace or remove systems by name
+//! instead of editing a hand-written call sequence.
+//!
+//! A `Schedule` only decides the order. What a system is, and how it gets
+//! run, is up to whoever owns the schedule.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    /// Turns land in the snakes' turn queues.
+    Input,
+    /// Headings change and bodies are pulled up behind their heads.
+    PreMove,
+    /// Heads are checked against the cells they are about to enter.
+    Collision,
+    /// Collisions become deaths, meals and growth.
+    Resolve,
+    /// Heads move, food spawns and the consequences of the tick are applied.
+    PostMove,
+    /// Draw commands are gathered from the world.
+    RenderGather,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 6] = [
+        Phase::Input,
+        Phase::PreMove,
+        Phase::Collision,
+        Phase::Resolve,
+        Phase::PostMove,
+        Phase::RenderGather,
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// The before and after constraints in a phase go round in a circle
+    /// through these systems.
+    Cycle(Vec<&'static str>),
+    /// `system` asks to run before or after `name`, which isn't registered.
+    Unknown {
+        system: &'static str,
+        name: &'static str,
+    },
+    /// `system` asks to run before or after `name` in another phase, against
+    /// the order of the phases.
+    PhaseOrder {
+        system: &'static str,
+        name: &'static str,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Entry<S> {
+    name: &'static str,
+    phase: Phase,
+    system: S,
+    before: Vec<&'static str>,
+    after: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Schedule<S> {
+    entries: Vec<Entry<S>>,
+    /// Indices into `entries` in running order, worked out again after any
+    /// change.
+    order: Option<Vec<usize>>,
+}
+
+impl<S> Default for Schedule<S> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            order: None,
+        }
+    }
+}
+
+/// A system that was just added, to attach ordering constraints to.
+pub struct SystemEntry<'a, S> {
+    schedule: &'a mut Schedule<S>,
+    index: usize,
+}
+
+impl<'a, S> SystemEntry<'a, S> {
+    /// Runs this system before `name`.
+    pub fn before(self, name: &'static str) -> Self {
+        self.schedule.entries[self.index].before.push(name);
+        self.schedule.order = None;
+        self
+    }
+
+    /// Runs this system after `name`.
+    pub fn after(self, name: &'static str) -> Self {
+        self.schedule.entries[self.index].after.push(name);
+        self.schedule.order = None;
+        self
+    }
+}
+
+impl<S: Copy> Schedule<S> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers `system` as `name` in `phase`. A system already registered
+    /// under that name is replaced, constraints and all.
+    pub fn add(&mut self, name: &'static str, phase: Phase, system: S) -> SystemEntry<'_, S> {
+        let entry = Entry {
+            name,
+            phase,
+            system,
+            before: Vec::new(),
+            after: Vec::new(),
+        };
+        let index = match self.entries.iter().position(|entry| entry.name == name) {
+            Some(index) => {
+                self.entries[index] = entry;
+                index
+            }
+            _ => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
+        };
+        self.order = None;
+        SystemEntry {
+            schedule: self,
+            index,
+        }
+    }
+
+    /// Takes the system registered as `name` out, returning whether there
+    /// was one. Constraints naming it go with it.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let count = self.entries.len();
+        self.entries.retain(|entry| entry.name != name);
+        for entry in self.entries.iter_mut() {
+            entry.before.retain(|other| *other != name);
+            entry.after.retain(|other| *other != name);
+        }
+        self.order = None;
+        self.entries.len() != count
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|entry| entry.name == name)
+    }
+
+    /// Names of the registered systems in the order they run.
+    pub fn names(&mut self) -> Result<Vec<&'static str>, ScheduleError> {
+        let order = self.order()?;
+        Ok(order
+            .iter()
+            .map(|index| self.entries[*index].name)
+            .collect())
+    }
+
+    /// The registered systems in the order they run.
+    pub fn systems(&mut self) -> Result<Vec<S>, ScheduleError> {
+        let order = self.order()?;
+        Ok(order
+            .iter()
+            .map(|index| self.entries[*index].system)
+            .collect())
+    }
+
+    fn order(&mut self) -> Result<Vec<usize>, ScheduleError> {
+        if let Some(order) = &self.order {
+            return Ok(order.clone());
+        }
+        self.check_constraints()?;
+        let mut order = Vec::with_capacity(self.entries.len());
+        for phase in Phase::ALL.iter() {
+            order.extend(self.sort_phase(*phase)?);
+        }
+        self.order = Some(order.clone());
+        Ok(order)
+    }
+
+    /// Checks every constraint names a registered system, and that the ones
+    /// reaching into other phases hold by the order of the phases alone.
+    fn check_constraints(&self) -> Result<(), ScheduleError> {
+        for entry in self.entries.iter() {
+            let constraints = entry
+                .before
+                .iter()
+                .map(|name| (*name, true))
+                .chain(entry.after.iter().map(|name| (*name, false)));
+            for (name, before) in constraints {
+                let other = match self.entries.iter().find(|other| other.name == name) {
+                    Some(other) => other,
+                    _ => {
+                        return Err(ScheduleError::Unknown {
+                            system: entry.name,
+                            name,
+                        })
+                    }
+                };
+                let contradicts = if before {
+                    entry.phase > other.phase
+                } else {
+                    entry.phase < other.phase
+                };
+                if contradicts {
+                    return Err(ScheduleError::PhaseOrder {
+                        system: entry.name,
+                        name,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Orders the systems of `phase` so every constraint holds, taking the
+    /// earliest added system whenever several are free to go next.
+    fn sort_phase(&self, phase: Phase) -> Result<Vec<usize>, ScheduleError> {
+        let members: Vec<usize> = (0..self.entries.len())
+            .filter(|index| self.entries[*index].phase == phase)
+            .collect();
+        let find = |name: &str| {
+            members
+                .iter()
+                .copied()
+                .find(|index| self.entries[*index].name == name)
+        };
+
+        // (earlier, later) pairs between systems of this phase
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for index in members.iter().copied() {
+            let entry = &self.entries[index];
+            edges.extend(
+                entry
+                    .before
+                    .iter()
+                    .filter_map(|name| find(name).map(|later| (index, later))),
+            );
+            edges.extend(
+                entry
+                    .after
+                    .iter()
+                    .filter_map(|name| find(name).map(|earlier| (earlier, index))),
+            );
+        }
+
+        let mut sorted = Vec::with_capacity(members.len());
+        let mut pending = members;
+        while !pending.is_empty() {
+            let next = pending.iter().position(|index| {
+                !edges
+                    .iter()
+                    .any(|(earlier, later)| later == index && pending.contains(earlier))
+            });
+            match next {
+                Some(position) => sorted.push(pending.remove(position)),
+                _ => {
+                    let names = pending.iter().map(|index| self.entries[*index].name);
+                    return Err(ScheduleError::Cycle(names.collect()));
+                }
+            }
+        }
+        Ok(sorted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule() -> Schedule<u32> {
+        let mut schedule = Schedule::new();
+        schedule.add("late", Phase::PostMove, 3);
+        schedule.add("early", Phase::Input, 1);
+        schedule.add("first", Phase::PreMove, 2).before("second");
+        schedule.add("second", Phase::PreMove, 4);
+        schedule
+    }
+
+    #[test]
+    fn phases_run_in_declared_order() {
+        let mut schedule = schedule();
+        assert_eq!(
+            schedule.names().unwrap(),
+            ["early", "first", "second", "late"]
+        );
+        assert_eq!(schedule.systems().unwrap(), [1, 2, 4, 3]);
+    }
+
+    #[test]
+    fn constraints_order_a_phase() {
+        let mut schedule = schedule();
+        schedule.add("third", Phase::PreMove, 5).before("first");
+        schedule.add("fourth", Phase::PreMove, 6).after("second");
+        schedule.add("unrelated", Phase::PreMove, 7);
+        assert_eq!(
+            schedule.names().unwrap(),
+            [
+                "early",
+                "third",
+                "first",
+                "second",
+                "fourth",
+                "unrelated",
+                "late"
+            ]
+        );
+    }
+
+    #[test]
+    fn constraints_across_phases_follow_the_phases() {
+        let mut schedule = schedule();
+        schedule.add("input", Phase::Input, 8).before("late");
+        assert_eq!(
+            schedule.names().unwrap(),
+            ["early", "input", "first", "second", "late"]
+        );
+        schedule.add("input", Phase::Input, 8).after("late");
+        assert_eq!(
+            schedule.names(),
+            Err(ScheduleError::PhaseOrder {
+                system: "input",
+                name: "late"
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_names_are_reported() {
+        let mut schedule = schedule();
+        schedule.add("input", Phase::Input, 8).after("missing");
+        assert_eq!(
+            schedule.systems(),
+            Err(ScheduleError::Unknown {
+                system: "input",
+                name: "missing"
+            })
+        );
+    }
+
+    #[test]
+    fn replace_and_remove_by_name() {
+        let mut schedule = schedule();
+        schedule.add("first", Phase::PostMove, 9);
+        assert_eq!(
+            schedule.names().unwrap(),
+            ["early", "second", "late", "first"]
+        );
+        assert!(schedule.remove("late"));
+        assert!(!schedule.remove("late"));
+        assert!(!schedule.contains("late"));
+        assert_eq!(schedule.systems().unwrap(), [1, 4, 9]);
+
+        // Nothing is left waiting on a system that is gone
+        schedule.add("third", Phase::PreMove, 5).after("second");
+        assert!(schedule.remove("second"));
+        assert_eq!(schedule.names().unwrap(), ["early", "third", "first"]);
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let mut schedule = schedule();
+        schedule.add("third", Phase::PreMove, 5).after("second");
+        schedule.add("second", Phase::PreMove, 4).after("third");
+        assert_eq!(
+            schedule.names(),
+            Err(ScheduleError::Cycle(vec!["second", "third"]))
+        );
+        schedule.remove("third");
+        assert!(schedule.systems().is_ok());
+    }
+}
@@ -0,0 +1,295 @@
+//! High score tables.
+//!
+//! Every game mode keeps its own table of the best `capacity` results. How
+//! the tables are kept between runs is up to a `ScoreStore`, so a platform
+//! without a filesystem can bring its own and tests can keep them in memory.
+use core::cmp::Reverse;
+use core::str::FromStr;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::textfile::{self, FileError};
+
+pub const SCORES_VERSION: u32 = 1;
+const MAGIC: &str = "snake-scores";
+
+/// Entries each table keeps unless asked otherwise.
+pub const DEFAULT_CAPACITY: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreEntry {
+    pub name: String,
+    pub score: i32,
+    pub length: usize,
+    pub seed: u64,
+    /// Seconds since the Unix epoch when the game ended.
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighScores {
+    capacity: usize,
+    tables: BTreeMap<String, Vec<ScoreEntry>>,
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        HighScores::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl HighScores {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tables: BTreeMap::new(),
+        }
+    }
+
+    /// Best entries for `mode`, highest score first.
+    pub fn top(&self, mode: &str) -> &[ScoreEntry] {
+        match self.tables.get(mode) {
+            Some(table) => table,
+            _ => &[],
+        }
+    }
+
+    /// Whether `score` would make it into the table for `mode`.
+    // `Option::is_none_or` is newer than the compilers this builds with
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn qualifies(&self, mode: &str, score: i32) -> bool {
+        let table = self.top(mode);
+        table.len() < self.capacity || table.last().map_or(true, |last| score > last.score)
+    }
+
+    /// Adds `entry` to the table for `mode` and returns its rank, starting at
+    /// zero, or `None` when it didn't make the cut. Ties rank below the
+    /// entries that got there first.
+    pub fn insert(&mut self, mode: &str, entry: ScoreEntry) -> Option<usize> {
+        if !self.qualifies(mode, entry.score) {
+            return None;
+        }
+        let table = self.tables.entry(mode.to_string()).or_default();
+        let rank = table
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(table.len());
+        table.insert(rank, entry);
+        table.truncate(self.capacity);
+        Some(rank)
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut out = String::new();
+        textfile::write_header(&mut out, MAGIC, SCORES_VERSION);
+        let _ = writeln!(out, "capacity {}", self.capacity);
+        for (mode, table) in self.tables.iter() {
+            for entry in table {
+                let _ = writeln!(
+                    out,
+                    "entry {} {} {} {} {} {}",
+                    mode, entry.score, entry.length, entry.seed, entry.timestamp, entry.name
+                );
+            }
+        }
+        out
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, FileError> {
+        let mut scores = HighScores::default();
+        for (number, line) in textfile::lines(contents, MAGIC, SCORES_VERSION)? {
+            if line.is_empty() {
+                continue;
+            }
+            let bad_line = || FileError::BadLine(number);
+            let (key, value) = line.split_once(' ').ok_or_else(bad_line)?;
+            match key {
+                "capacity" => {
+                    let capacity = usize::from_str(value).map_err(|_| bad_line())?;
+                    scores.capacity = capacity.max(1);
+                }
+                "entry" => {
+                    let (mode, entry) = parse_entry(value).ok_or_else(bad_line)?;
+                    scores.tables.entry(mode).or_default().push(entry);
+                }
+                _ => return Err(bad_line()),
+            }
+        }
+        for table in scores.tables.values_mut() {
+            // Stable, so ties keep the order they were saved in
+            table.sort_by_key(|entry| Reverse(entry.score));
+            table.truncate(scores.capacity);
+        }
+        Ok(scores)
+    }
+}
+
+fn parse_entry(value: &str) -> Option<(String, ScoreEntry)> {
+    let mut parts = value.splitn(6, ' ');
+    let mode = parts.next()?.to_string();
+    let score = i32::from_str(parts.next()?).ok()?;
+    let length = usize::from_str(parts.next()?).ok()?;
+    let seed = u64::from_str(parts.next()?).ok()?;
+    let timestamp = u64::from_str(parts.next()?).ok()?;
+    let name = parts.next().unwrap_or("").to_string();
+    Some((
+        mode,
+        ScoreEntry {
+            name,
+            score,
+            length,
+            seed,
+            timestamp,
+        },
+    ))
+}
+
+/// Somewhere high scores are kept between runs.
+pub trait ScoreStore {
+    /// The saved tables, or empty ones when nothing was saved yet.
+    fn load(&self) -> Result<HighScores, FileError>;
+    fn save(&mut self, scores: &HighScores) -> Result<(), FileError>;
+}
+
+/// Keeps the tables for as long as the store lives, written out the same way
+/// a `FileStore` would.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    contents: Option<String>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl ScoreStore for MemoryStore {
+    fn load(&self) -> Result<HighScores, FileError> {
+        match &self.contents {
+            Some(contents) => HighScores::parse(contents),
+            _ => Ok(HighScores::default()),
+        }
+    }
+
+    fn save(&mut self, scores: &HighScores) -> Result<(), FileError> {
+        self.contents = Some(scores.serialize());
+        Ok(())
+    }
+}
+
+/// Keeps the tables in a text file, creating its directory when saving.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl ScoreStore for FileStore {
+    fn load(&self) -> Result<HighScores, FileError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) => HighScores::parse(&contents),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(HighScores::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn save(&mut self, scores: &HighScores) -> Result<(), FileError> {
+        textfile::save(&self.path, &scores.serialize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: i32) -> ScoreEntry {
+        ScoreEntry {
+            name: name.to_string(),
+            score,
+            length: score as usize + 2,
+            seed: 42,
+            timestamp: 1_600_000_000,
+        }
+    }
+
+    fn names(scores: &HighScores, mode: &str) -> Vec<String> {
+        scores.top(mode).iter().map(|e| e.name.clone()).collect()
+    }
+
+    #[test]
+    fn insert_ranks_by_score() {
+        let mut scores = HighScores::new(5);
+        assert_eq!(scores.insert("classic", entry("a", 5)), Some(0));
+        assert_eq!(scores.insert("classic", entry("b", 9)), Some(0));
+        assert_eq!(scores.insert("classic", entry("c", 7)), Some(1));
+        assert_eq!(scores.insert("classic", entry("d", 1)), Some(3));
+        assert_eq!(names(&scores, "classic"), ["b", "c", "a", "d"]);
+        assert!(scores.top("speed").is_empty());
+    }
+
+    #[test]
+    fn ties_rank_below_earlier_entries() {
+        let mut scores = HighScores::new(5);
+        scores.insert("classic", entry("first", 4));
+        scores.insert("classic", entry("low", 2));
+        assert_eq!(scores.insert("classic", entry("second", 4)), Some(1));
+        assert_eq!(names(&scores, "classic"), ["first", "second", "low"]);
+    }
+
+    #[test]
+    fn full_table_cuts_the_lowest() {
+        let mut scores = HighScores::new(3);
+        for (name, score) in [("a", 3), ("b", 2), ("c", 1)].iter() {
+            scores.insert("classic", entry(name, *score));
+        }
+        assert!(!scores.qualifies("classic", 1));
+        assert_eq!(scores.insert("classic", entry("tie", 1)), None);
+        assert_eq!(scores.insert("classic", entry("d", 2)), Some(2));
+        assert_eq!(names(&scores, "classic"), ["a", "b", "d"]);
+        assert!(scores.qualifies("speed", 0));
+    }
+
+    #[test]
+    fn round_trip_through_memory_store() {
+        let mut scores = HighScores::new(4);
+        scores.insert("classic", entry("Player 1", 12));
+        scores.insert("classic", entry("", 3));
+        scores.insert("classic", entry("tied", 3));
+        scores.insert("zen", entry("calm", 30));
+        assert_eq!(HighScores::parse(&scores.serialize()).unwrap(), scores);
+
+        let mut store = MemoryStore::new();
+        assert_eq!(store.load().unwrap(), HighScores::default());
+        store.save(&scores).unwrap();
+        assert_eq!(store.load().unwrap(), scores);
+    }
+
+    #[test]
+    fn parse_rejects_bad_files() {
+        assert!(matches!(
+            HighScores::parse("capacity 3"),
+            Err(FileError::MissingHeader)
+        ));
+        assert!(matches!(
+            HighScores::parse("snake-scores 99"),
+            Err(FileError::UnsupportedVersion(99))
+        ));
+        assert!(matches!(
+            HighScores::parse("snake-scores 1\nentry classic x"),
+            Err(FileError::BadLine(2))
+        ));
+    }
+}
@@ -0,0 +1,1350 @@
+use glam::Vec2;
+
+use crate::ai::Controller;
+use crate::arena::{self, Arena};
+use crate::clock::{self, GameClock, Timer};
+use crate::components;
+use crate::components::Player;
+use crate::events::{
+    Collision, DeathCause, Events, GameEnded, PickedUp, Reader, SnakeDied, SnakeEatFood,
+};
+use crate::food::{Effect, FoodKind};
+use crate::grid::Grid;
+use crate::level::Level;
+use crate::powerup::{self, PowerUp, Status};
+use crate::rng::Rng;
+use crate::rules::{Measure, RuleSet};
+use crate::schedule::{Phase, Schedule};
+
+/// Per-player bookkeeping that outlives the snake entities.
+#[derive(Debug)]
+pub struct PlayerState {
+    pub player: Player,
+    pub score: i32,
+    pub alive: bool,
+    /// Tick of the current game the player's snake died on.
+    pub died_at: Option<u64>,
+    pub cause: Option<DeathCause>,
+    /// Snake length, head included, as of its death.
+    pub length: usize,
+    /// Steers the player's snake when set, instead of a human.
+    pub controller: Option<Box<dyn Controller>>,
+}
+
+impl PlayerState {
+    pub fn new(player: Player) -> Self {
+        Self {
+            player,
+            score: 0,
+            alive: true,
+            died_at: None,
+            cause: None,
+            length: 0,
+            controller: None,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.score = 0;
+        self.alive = true;
+        self.died_at = None;
+        self.cause = None;
+        self.length = 0;
+    }
+}
+
+/// How a game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    GameOver,
+    /// The snakes filled the whole board.
+    Win,
+}
+
+/// One player's share of a finished game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerResult {
+    pub player: Player,
+    pub score: i32,
+    pub length: usize,
+    /// Seconds the player's snake stayed alive.
+    pub survived: f64,
+    /// What killed the snake, `None` if it was still alive at the end.
+    pub cause: Option<DeathCause>,
+}
+
+/// Where each gameplay system is up to in the channels it reads.
+#[derive(Debug, Default)]
+struct Cursors {
+    score: Reader,
+    despawn_food: Reader,
+    effects: Reader,
+    power_ups: Reader,
+    deaths: Reader,
+    collisions: Reader,
+    game_over: Reader,
+}
+
+/// Which ticks a gameplay system runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOn {
+    EveryTick,
+    /// Ticks the move timer finishes on, when the snakes take a step.
+    Move,
+    /// Ticks the food timer finishes on.
+    FoodSpawn,
+}
+
+/// A gameplay system as registered in the `GameWorld` schedule.
+#[derive(Clone, Copy)]
+pub struct System {
+    pub run: fn(&mut GameWorld),
+    pub on: RunOn,
+}
+
+impl System {
+    pub fn every_tick(run: fn(&mut GameWorld)) -> Self {
+        Self {
+            run,
+            on: RunOn::EveryTick,
+        }
+    }
+
+    pub fn on_move(run: fn(&mut GameWorld)) -> Self {
+        Self {
+            run,
+            on: RunOn::Move,
+        }
+    }
+
+    pub fn on_food_spawn(run: fn(&mut GameWorld)) -> Self {
+        Self {
+            run,
+            on: RunOn::FoodSpawn,
+        }
+    }
+}
+
+impl core::fmt::Debug for System {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("System").field("on", &self.on).finish()
+    }
+}
+
+/// The gameplay systems `tick_systems` runs, in the order that reproduces
+/// recorded games.
+pub fn default_schedule() -> Schedule<System> {
+    let mut schedule = Schedule::new();
+    schedule.add(
+        "think",
+        Phase::Input,
+        System::every_tick(crate::ai::think_system),
+    );
+    schedule
+        .add("input", Phase::Input, System::every_tick(input_system))
+        .after("think");
+
+    schedule.add(
+        "update_velocity_direction",
+        Phase::PreMove,
+        System::on_move(update_velocity_direction),
+    );
+    schedule
+        .add(
+            "body_movement",
+            Phase::PreMove,
+            System::on_move(body_movement_system),
+        )
+        .after("update_velocity_direction");
+
+    schedule.add(
+        "head_collision",
+        Phase::Collision,
+        System::on_move(head_collision_system),
+    );
+
+    schedule.add(
+        "handle_collision",
+        Phase::Resolve,
+        System::on_move(handle_collision_system),
+    );
+    schedule
+        .add(
+            "food_effects",
+            Phase::Resolve,
+            System::on_move(food_effect_system),
+        )
+        .after("handle_collision");
+    schedule
+        .add(
+            "power_ups",
+            Phase::Resolve,
+            System::on_move(power_up_system),
+        )
+        .after("handle_collision");
+
+    schedule.add(
+        "movement",
+        Phase::PostMove,
+        System::on_move(movement_system),
+    );
+    schedule
+        .add("magnet", Phase::PostMove, System::on_move(magnet_system))
+        .after("movement");
+    // Food only spawns in cells the heads have already moved out of or into
+    schedule
+        .add(
+            "add_food",
+            Phase::PostMove,
+            System::on_food_spawn(add_food_system),
+        )
+        .after("movement");
+    schedule
+        .add(
+            "add_pickup",
+            Phase::PostMove,
+            System::on_food_spawn(add_pickup_system),
+        )
+        .after("movement");
+    schedule.add(
+        "despawn_food",
+        Phase::PostMove,
+        System::every_tick(despawn_food_system),
+    );
+    schedule.add("expire", Phase::PostMove, System::every_tick(expire_system));
+    schedule.add(
+        "snake_death",
+        Phase::PostMove,
+        System::every_tick(snake_death_system),
+    );
+    // Only the snakes still alive after the move fill the board
+    schedule
+        .add(
+            "board_full",
+            Phase::PostMove,
+            System::on_move(board_full_system),
+        )
+        .after("movement")
+        .after("snake_death");
+    schedule.add(
+        "update_score",
+        Phase::PostMove,
+        System::every_tick(update_score_system),
+    );
+    schedule
+        .add("speed", Phase::PostMove, System::every_tick(speed_system))
+        .after("update_score");
+    schedule.add(
+        "power_up_text",
+        Phase::PostMove,
+        System::every_tick(power_up_text_system),
+    );
+    schedule
+        .add(
+            "game_over",
+            Phase::PostMove,
+            System::every_tick(game_over_system),
+        )
+        .after("snake_death")
+        .after("board_full");
+    schedule
+}
+
+pub struct GameWorld {
+    pub world: hecs::World,
+    pub events: Events,
+    cursors: Cursors,
+    /// Systems run by every `tick_systems`.
+    pub schedule: Schedule<System>,
+    /// Input fed to the snakes on the current tick. Cleared once the tick is
+    /// done, so presses don't carry over to the next one.
+    pub input: components::Input,
+    /// What `input` held at the end of the last tick, AI turns included.
+    pub last_input: components::Input,
+    pub camera: components::Camera2D,
+    /// What the systems play by, see `set_rules` when changing it.
+    pub rules: RuleSet,
+    /// Walls placed by `create_level_system` at the start of every game.
+    pub level: Level,
+    pub arena: Arena,
+    /// Occupancy of `arena`, see `set_arena` when changing it.
+    pub grid: Grid,
+    pub clock: GameClock,
+    /// Seconds between moves right now, the rules' move interval taken down
+    /// their speed curve.
+    pub move_interval: f64,
+    pub move_timer: Timer,
+    pub food_timer: Timer,
+    pub rng: Rng,
+    /// Seed every new game starts from. When unset each game after the
+    /// first draws a fresh seed from the previous one.
+    pub fixed_seed: Option<u64>,
+    pub players: Vec<PlayerState>,
+    /// Ticks simulated since the current game started.
+    pub ticks: u64,
+    /// Set once the current game has ended, until `restart_system` runs.
+    pub outcome: Option<Outcome>,
+}
+
+impl GameWorld {
+    pub fn new(camera: components::Camera2D, clock: GameClock, seed: u64) -> Self {
+        let step = clock.step();
+        let rules = RuleSet::default();
+        GameWorld {
+            events: Events::default(),
+            cursors: Cursors::default(),
+            schedule: default_schedule(),
+            input: components::Input::default(),
+            last_input: components::Input::default(),
+            camera,
+            arena: rules.arena,
+            grid: Grid::new(&rules.arena),
+            world: hecs::World::new(),
+            move_interval: rules.move_interval,
+            move_timer: Timer::new(rules.move_interval, step),
+            food_timer: Timer::new(rules.food_interval, step),
+            rules,
+            level: Level::new(),
+            clock,
+            rng: Rng::new(seed),
+            fixed_seed: None,
+            players: vec![PlayerState::new(Player(0))],
+            ticks: 0,
+            outcome: None,
+        }
+    }
+
+    /// Results of the current game for every player.
+    pub fn results(&self) -> Vec<PlayerResult> {
+        let step = self.clock.step();
+        self.players
+            .iter()
+            .map(|state| {
+                let length = if state.alive {
+                    self.world
+                        .query::<(&Player, &components::Snake, Option<&components::Body>)>()
+                        .iter()
+                        .find(|(_, (owner, _, _))| **owner == state.player)
+                        .map_or(0, |(_, (_, _, body))| 1 + body.map_or(0, |body| body.len()))
+                } else {
+                    state.length
+                };
+                PlayerResult {
+                    player: state.player,
+                    score: state.score,
+                    length,
+                    survived: state.died_at.unwrap_or(self.ticks) as f64 * step,
+                    cause: state.cause,
+                }
+            })
+            .collect()
+    }
+
+    /// Plays by `rules` from now on, arena and timers included.
+    pub fn set_rules(&mut self, rules: RuleSet) {
+        let step = self.clock.step();
+        self.move_interval = rules.move_interval;
+        self.move_timer.set_duration(rules.move_interval, step);
+        self.food_timer.set_duration(rules.food_interval, step);
+        self.rules = rules;
+        self.set_arena(rules.arena);
+    }
+
+    pub fn set_arena(&mut self, arena: Arena) {
+        self.rules.arena = arena;
+        self.arena = arena;
+        self.grid = Grid::new(&arena);
+        reindex_grid_system(self);
+    }
+
+    pub fn set_player_count(&mut self, count: usize) {
+        self.players = (0..count.max(1))
+            .map(|id| PlayerState::new(Player(id)))
+            .collect();
+    }
+}
+
+/// Rebuilds the grid from scratch out of every solid entity in the world.
+pub fn reindex_grid_system(game_world: &mut GameWorld) {
+    let GameWorld { world, grid, .. } = game_world;
+    grid.clear();
+    for (ent, (pos, col, head, body)) in &mut world.query::<(
+        &components::Position,
+        Option<&components::Collision>,
+        Option<&components::Snake>,
+        Option<&components::Body>,
+    )>() {
+        if col.is_some() || head.is_some() {
+            grid.insert(ent, arena::cell(pos.0));
+        }
+        for cell in body.iter().flat_map(|body| body.segments()) {
+            grid.insert(ent, cell);
+        }
+    }
+}
+
+/// Puts an obstacle on every wall of the level that lies inside the arena.
+pub fn create_level_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        level,
+        arena,
+        grid,
+        ..
+    } = game_world;
+    for cell in level.walls() {
+        if !arena.contains(Vec2::new(cell.0 as f32, cell.1 as f32)) {
+            continue;
+        }
+        let wall = world.spawn(components::Obstacle::bundle(cell));
+        grid.insert(wall, cell);
+    }
+}
+
+/// Spawns a snake for every player, spread evenly along the arena's width.
+/// Bodies trail straight down from the heads, whatever doesn't fit above the
+/// bottom edge is grown over the first moves instead.
+pub fn create_snake_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        players,
+        rules,
+        level,
+        arena,
+        grid,
+        ..
+    } = game_world;
+    let count = players.len() as i32;
+    let segments = rules.start_length.saturating_sub(1) as i32;
+    let fits = segments.min(-arena.bottom()).max(0);
+    let mut taken: Vec<i32> = Vec::with_capacity(players.len());
+    for (index, state) in players.iter().enumerate() {
+        let player = state.player;
+        let spread = arena.left() + (index as i32 + 1) * arena.width / (count + 1);
+        let x = spawn_column(arena, level, spread, fits, &taken);
+        taken.push(x);
+        let cells: Vec<(i32, i32)> = (1..=fits).map(|y| (x, -y)).collect();
+        let mut body = components::Body::new(
+            cells.iter().copied(),
+            player.material("Tail"),
+            components::Mesh("Tail".into()),
+        );
+        body.grow((segments - fits) as u32);
+        let head = world.spawn((
+            components::Snake,
+            player,
+            components::Position(Vec2::new(x as f32, 0.)),
+            components::Velocity(Vec2::new(0., 1.)),
+            components::HeadDirection::default(),
+            components::TurnQueue::default(),
+            player.material("Snake"),
+            components::Mesh("Snake".into()),
+            body,
+        ));
+        grid.insert(head, (x, 0));
+        for cell in cells {
+            grid.insert(head, cell);
+        }
+    }
+}
+
+/// Cells ahead of a new snake that `spawn_column` tries to keep clear of
+/// walls.
+const SPAWN_CLEARANCE: i32 = 5;
+
+/// The column nearest `preferred` where a snake `fits` segments long can
+/// start out without sitting on a wall or heading straight into one. Falls
+/// back on the columns with the most room ahead when the level leaves none
+/// fully clear.
+fn spawn_column(arena: &Arena, level: &Level, preferred: i32, fits: i32, taken: &[i32]) -> i32 {
+    let room = |x: i32| -> Option<usize> {
+        if taken.contains(&x) || (-fits..=0).any(|y| level.is_wall((x, y))) {
+            return None;
+        }
+        let ahead = (1..=SPAWN_CLEARANCE.min(arena.top()))
+            .take_while(|y| !level.is_wall((x, *y)))
+            .count();
+        Some(ahead)
+    };
+    (arena.left()..=arena.right())
+        .filter_map(|x| room(x).map(|ahead| (x, ahead)))
+        .max_by_key(|(x, ahead)| (*ahead, -(x - preferred).abs()))
+        .map_or(preferred, |(x, _)| x)
+}
+
+/// Feeds the tick's `GameWorld::input` to the snakes.
+pub fn input_system(game_world: &mut GameWorld) {
+    let input = game_world.input;
+    update_input(game_world, &input);
+}
+
+pub fn update_input(game_world: &mut GameWorld, input: &components::Input) {
+    let GameWorld { world, .. } = game_world;
+    for (_, (player, dir, queue, _)) in &mut world.query::<(
+        &Player,
+        &components::HeadDirection,
+        &mut components::TurnQueue,
+        &components::Snake,
+    )>() {
+        for (_, turn) in input.turns().filter(|(owner, _)| owner == player) {
+            queue.push(turn, dir.0);
+        }
+    }
+}
+
+pub fn add_food_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        rng,
+        rules,
+        arena,
+        grid,
+        clock,
+        ticks,
+        ..
+    } = game_world;
+    let food_count = world.query::<&components::Food>().iter().count();
+    if food_count >= rules.food_cap {
+        return;
+    }
+
+    let pos = match free_cell(arena, rng, grid) {
+        Some(pos) => components::Position(pos),
+        _ => return,
+    };
+    let cell = arena::cell(pos.0);
+    let kind = FoodKind::pick(rng);
+    let food = world.spawn((
+        pos,
+        components::Collision::food(),
+        components::Food(kind),
+        kind.material(),
+        components::Mesh("Food".into()),
+    ));
+    if let Some(lifetime) = kind.spec().lifetime {
+        let tick = *ticks + clock::ticks_for(lifetime, clock.step()) as u64;
+        world
+            .insert_one(food, components::Expires { tick })
+            .expect("Food was just spawned");
+    }
+    grid.insert(food, cell);
+}
+
+/// Now and then puts a random power-up on the board, one at a time.
+pub fn add_pickup_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        rng,
+        arena,
+        grid,
+        clock,
+        ticks,
+        ..
+    } = game_world;
+    if world.query::<&components::Pickup>().iter().next().is_some() {
+        return;
+    }
+    if rng.gen_range(0, powerup::SPAWN_ODDS) != 0 {
+        return;
+    }
+    let pos = match free_cell(arena, rng, grid) {
+        Some(pos) => pos,
+        _ => return,
+    };
+    let power = PowerUp::ALL[rng.gen_range(0, PowerUp::ALL.len() as i32) as usize];
+    let tick = *ticks + clock::ticks_for(powerup::PICKUP_LIFETIME, clock.step()) as u64;
+    let pickup = world.spawn((
+        components::Position(pos),
+        components::Collision::pickup(),
+        components::Pickup(power),
+        power.material(),
+        components::Mesh("Pickup".into()),
+        components::Expires { tick },
+    ));
+    grid.insert(pickup, arena::cell(pos));
+}
+
+/// Picks a cell nothing occupies. A few blind guesses cover the usual mostly
+/// empty board, after that only the free cells are sampled.
+fn free_cell(arena: &Arena, rng: &mut Rng, grid: &Grid) -> Option<Vec2> {
+    for _ in 0..8 {
+        let pos = arena.random_cell(rng);
+        if grid.is_free(arena::cell(pos)) {
+            return Some(pos);
+        }
+    }
+    let free: Vec<Vec2> = arena
+        .cells()
+        .filter(|pos| grid.is_free(arena::cell(*pos)))
+        .collect();
+    if free.is_empty() {
+        return None;
+    }
+    let index = rng.gen_range(0, free.len() as i32) as usize;
+    Some(free[index])
+}
+
+pub fn update_velocity_direction(game_world: &mut GameWorld) {
+    let GameWorld { world, .. } = game_world;
+    for (_, (velocity, direction, queue)) in &mut world.query::<(
+        &mut components::Velocity,
+        &mut components::HeadDirection,
+        Option<&mut components::TurnQueue>,
+    )>() {
+        if let Some(turn) = queue.and_then(|queue| queue.pop()) {
+            direction.0 = turn;
+        }
+        velocity.0 = direction.0.velocity();
+    }
+}
+
+pub fn movement_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world, arena, grid, ..
+    } = game_world;
+    for (ent, (pos, velocity)) in
+        &mut world.query::<(&mut components::Position, &components::Velocity)>()
+    {
+        // Only a shield gets a snake past the edge, it stops there instead
+        let next = match arena.step(pos.0, velocity.0) {
+            Some(next) => next,
+            _ => continue,
+        };
+        grid.relocate(ent, arena::cell(pos.0), arena::cell(next));
+        pos.0 = next;
+    }
+}
+
+/// Pulls every body up behind its head, ahead of the head itself moving.
+/// A head that is about to stop at the edge keeps its body where it is.
+pub fn body_movement_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world, arena, grid, ..
+    } = game_world;
+    for (ent, (pos, velocity, body)) in &mut world.query::<(
+        &components::Position,
+        &components::Velocity,
+        &mut components::Body,
+    )>() {
+        if arena.step(pos.0, velocity.0).is_none() {
+            continue;
+        }
+        let neck = arena::cell(pos.0);
+        grid.insert(ent, neck);
+        if let Some(tip) = body.advance(neck) {
+            grid.remove(ent, tip);
+        }
+    }
+}
+
+pub fn update_score_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        events,
+        cursors,
+        players,
+        ..
+    } = game_world;
+    for SnakeEatFood { player, kind, .. } in events.food_eaten.read(&mut cursors.score) {
+        if let Some(state) = players.get_mut(player.0) {
+            state.score += kind.spec().points;
+        }
+    }
+    update_score_text(world, players);
+}
+
+fn update_score_text(world: &mut hecs::World, players: &[PlayerState]) {
+    for (_, (text, score)) in &mut world.query::<(&mut components::Text, &components::Score)>() {
+        if let Some(state) = players.get((score.0).0) {
+            let label = format!("Score:  {}", state.score);
+            if text.text() != label {
+                text.update_text(label);
+            }
+        }
+    }
+}
+
+/// Takes the move interval down the rules' speed curve as the players make
+/// progress, scaled by whatever paces and slow-mo are running.
+pub fn speed_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        rules,
+        players,
+        clock,
+        move_interval,
+        move_timer,
+        ticks,
+        ..
+    } = game_world;
+    let progress = match rules.speed.measure {
+        Measure::Score => players
+            .iter()
+            .map(|state| state.score.max(0) as usize)
+            .max()
+            .unwrap_or(0),
+        Measure::Length => world
+            .query::<(&components::Snake, Option<&components::Body>)>()
+            .iter()
+            .map(|(_, (_, body))| 1 + body.map_or(0, |body| body.len()))
+            .max()
+            .unwrap_or(0),
+    };
+    let pace: f64 = world
+        .query::<&components::Pace>()
+        .iter()
+        .filter(|(_, pace)| pace.tick > *ticks)
+        .map(|(_, pace)| pace.scale)
+        .product();
+    let slow_mo = world
+        .query::<&components::SlowMo>()
+        .iter()
+        .any(|(_, slow_mo)| slow_mo.tick > *ticks);
+    let pace = if slow_mo {
+        pace * powerup::SLOW_MO_SCALE
+    } else {
+        pace
+    };
+    let interval = rules.speed.interval(rules.move_interval, progress as u32) * pace;
+    *move_interval = interval;
+    move_timer.set_duration(interval, clock.step());
+    update_speed_text(world, *move_interval);
+}
+
+fn update_speed_text(world: &mut hecs::World, move_interval: f64) {
+    let label = format!("Speed:  {:.1}/s", 1. / move_interval);
+    for (_, (text, _)) in &mut world.query::<(&mut components::Text, &components::Speed)>() {
+        if text.text() != label {
+            text.update_text(label.clone());
+        }
+    }
+}
+
+pub fn despawn_food_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        grid,
+        events,
+        cursors,
+        ..
+    } = game_world;
+    for SnakeEatFood { entity, .. } in events.food_eaten.read(&mut cursors.despawn_food) {
+        // Taken out where the food is now, in case it moved since it was eaten
+        let cell = match world.get::<components::Position>(*entity) {
+            Ok(pos) => arena::cell(pos.0),
+            _ => continue,
+        };
+        grid.remove(*entity, cell);
+        world.despawn(*entity).expect("Entity was just queried");
+    }
+}
+
+/// Removes the snakes that died this move and ends the game once too few
+/// are left.
+pub fn snake_death_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        grid,
+        events,
+        cursors,
+        players,
+        ticks,
+        ..
+    } = game_world;
+    let deaths: Vec<(Player, DeathCause)> = events
+        .deaths
+        .read(&mut cursors.deaths)
+        .map(|death| (death.player, death.cause))
+        .collect();
+    if deaths.is_empty() {
+        return;
+    }
+    let dead: Vec<Player> = deaths.iter().map(|(player, _)| *player).collect();
+    let mut lengths: Vec<(Player, usize)> = Vec::with_capacity(dead.len());
+
+    let remains: Vec<hecs::Entity> = world
+        .query::<(
+            &Player,
+            &components::Position,
+            &components::Snake,
+            Option<&components::Body>,
+        )>()
+        .iter()
+        .filter(|(_, (player, _, _, _))| dead.contains(player))
+        .map(|(ent, (player, pos, _, body))| {
+            lengths.push((*player, 1 + body.map_or(0, |body| body.len())));
+            grid.remove(ent, arena::cell(pos.0));
+            for cell in body.iter().flat_map(|body| body.segments()) {
+                grid.remove(ent, cell);
+            }
+            ent
+        })
+        .collect();
+    for ent in remains {
+        world.despawn(ent).expect("Entity was just queried");
+    }
+    for state in players.iter_mut() {
+        // With several causes for one snake, the first one reported wins
+        if let Some((_, cause)) = deaths.iter().find(|(player, _)| *player == state.player) {
+            if !state.alive {
+                continue;
+            }
+            state.alive = false;
+            state.died_at = Some(*ticks);
+            state.cause = Some(*cause);
+            state.length = lengths
+                .iter()
+                .find(|(player, _)| *player == state.player)
+                .map_or(0, |(_, length)| *length);
+        }
+    }
+
+    // Alone you play until you die, together until one snake is left
+    let last_standing = if players.len() > 1 { 1 } else { 0 };
+    if players.iter().filter(|state| state.alive).count() <= last_standing {
+        events.game_ended.send(GameEnded(Outcome::GameOver));
+    }
+}
+
+/// Applies the effect of everything eaten this move to the snake that ate
+/// it.
+pub fn food_effect_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        grid,
+        events,
+        cursors,
+        rules,
+        clock,
+        ticks,
+        ..
+    } = game_world;
+    let eaten: Vec<SnakeEatFood> = events
+        .food_eaten
+        .read(&mut cursors.effects)
+        .copied()
+        .collect();
+    for SnakeEatFood { player, kind, .. } in eaten {
+        let head = world
+            .query::<(&Player, &components::Snake)>()
+            .iter()
+            .find(|(_, (owner, _))| **owner == player)
+            .map(|(ent, _)| ent);
+        let head = match head {
+            Some(head) => head,
+            _ => continue,
+        };
+        match kind.spec().effect {
+            Effect::Grow => {
+                if let Ok(mut body) = world.get_mut::<components::Body>(head) {
+                    body.grow(rules.growth);
+                }
+            }
+            Effect::Shrink(segments) => {
+                if let Ok(mut body) = world.get_mut::<components::Body>(head) {
+                    for cell in body.shrink(segments) {
+                        grid.remove(head, cell);
+                    }
+                }
+            }
+            Effect::Pace { scale, seconds } => {
+                let tick = *ticks + clock::ticks_for(seconds, clock.step()) as u64;
+                world
+                    .insert_one(head, components::Pace { scale, tick })
+                    .expect("Entity was just queried");
+            }
+        }
+    }
+}
+
+/// Takes food and pickups off the board once their time is up, and ends
+/// statuses that ran out.
+pub fn expire_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world, grid, ticks, ..
+    } = game_world;
+    let expired: Vec<(hecs::Entity, Option<(i32, i32)>)> = world
+        .query::<(&components::Expires, Option<&components::Position>)>()
+        .iter()
+        .filter(|(_, (expires, _))| expires.tick <= *ticks)
+        .map(|(ent, (_, pos))| (ent, pos.map(|pos| arena::cell(pos.0))))
+        .collect();
+    for (ent, cell) in expired {
+        if let Some(cell) = cell {
+            grid.remove(ent, cell);
+        }
+        world.despawn(ent).expect("Entity was just queried");
+    }
+
+    expire_status::<components::Pace>(world, *ticks);
+    expire_status::<components::Ghost>(world, *ticks);
+    expire_status::<components::SlowMo>(world, *ticks);
+    expire_status::<components::Magnet>(world, *ticks);
+    expire_status::<components::Shield>(world, *ticks);
+}
+
+fn expire_status<T: Status>(world: &mut hecs::World, ticks: u64) {
+    let finished: Vec<hecs::Entity> = world
+        .query::<&T>()
+        .iter()
+        .filter(|(_, status)| status.until() <= ticks)
+        .map(|(ent, _)| ent)
+        .collect();
+    for ent in finished {
+        world.remove_one::<T>(ent).expect("Entity was just queried");
+    }
+}
+
+/// Hands out the power-ups picked up this move and clears the pickups away.
+pub fn power_up_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        grid,
+        events,
+        cursors,
+        clock,
+        ticks,
+        ..
+    } = game_world;
+    let picked: Vec<PickedUp> = events
+        .pickups
+        .read(&mut cursors.power_ups)
+        .copied()
+        .collect();
+    for PickedUp {
+        entity,
+        pos,
+        player,
+        power,
+    } in picked
+    {
+        // Already handed out to someone else
+        if world.despawn(entity).is_err() {
+            continue;
+        }
+        grid.remove(entity, arena::cell(pos));
+        let head = world
+            .query::<(&Player, &components::Snake)>()
+            .iter()
+            .find(|(_, (owner, _))| **owner == player)
+            .map(|(ent, _)| ent);
+        let head = match head {
+            Some(head) => head,
+            _ => continue,
+        };
+        let tick = *ticks + clock::ticks_for(power.duration(), clock.step()) as u64;
+        let granted = match power {
+            PowerUp::Ghost => world.insert_one(head, components::Ghost { tick }),
+            PowerUp::SlowMo => world.insert_one(head, components::SlowMo { tick }),
+            PowerUp::Magnet => world.insert_one(head, components::Magnet { tick }),
+            PowerUp::Shield => world.insert_one(head, components::Shield { tick }),
+        };
+        granted.expect("Entity was just queried");
+    }
+}
+
+/// Pulls food within reach of a magnetised head one cell closer on every
+/// move, as long as the cell it is pulled into is free.
+pub fn magnet_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        grid,
+        events,
+        ticks,
+        ..
+    } = game_world;
+    let magnets: Vec<(i32, i32)> = world
+        .query::<(&components::Position, &components::Magnet)>()
+        .iter()
+        .filter(|(_, (_, magnet))| magnet.tick > *ticks)
+        .map(|(_, (pos, _))| arena::cell(pos.0))
+        .collect();
+    if magnets.is_empty() {
+        return;
+    }
+    // Food eaten on this move stays where the snake found it
+    let eaten: Vec<hecs::Entity> = events.food_eaten.iter().map(|meal| meal.entity).collect();
+    for (ent, (pos, _)) in &mut world.query::<(&mut components::Position, &components::Food)>() {
+        if eaten.contains(&ent) {
+            continue;
+        }
+        let from = arena::cell(pos.0);
+        let (dx, dy) = match magnets
+            .iter()
+            .map(|head| (head.0 - from.0, head.1 - from.1))
+            .filter(|(dx, dy)| {
+                dx.abs() <= powerup::MAGNET_RANGE && dy.abs() <= powerup::MAGNET_RANGE
+            })
+            .min_by_key(|(dx, dy)| dx.abs() + dy.abs())
+        {
+            Some(delta) => delta,
+            _ => continue,
+        };
+        let to = if dx.abs() >= dy.abs() {
+            (from.0 + dx.signum(), from.1)
+        } else {
+            (from.0, from.1 + dy.signum())
+        };
+        if !grid.is_free(to) {
+            continue;
+        }
+        grid.relocate(ent, from, to);
+        pos.0 = Vec2::new(to.0 as f32, to.1 as f32);
+    }
+}
+
+/// Lists the power-ups every player has running, with the seconds left.
+pub fn power_up_text_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        clock,
+        ticks,
+        ..
+    } = game_world;
+    let step = clock.step();
+    let remaining = |until: Option<u64>| {
+        until
+            .filter(|until| *until > *ticks)
+            .map(|until| ((until - *ticks) as f64 * step).ceil())
+    };
+    let labels: Vec<(Player, String)> = world
+        .query::<(
+            &components::Snake,
+            &Player,
+            Option<&components::Ghost>,
+            Option<&components::SlowMo>,
+            Option<&components::Magnet>,
+            Option<&components::Shield>,
+        )>()
+        .iter()
+        .map(|(_, (_, player, ghost, slow_mo, magnet, shield))| {
+            let active: Vec<String> = [
+                (PowerUp::Ghost, ghost.map(Status::until)),
+                (PowerUp::SlowMo, slow_mo.map(Status::until)),
+                (PowerUp::Magnet, magnet.map(Status::until)),
+                (PowerUp::Shield, shield.map(Status::until)),
+            ]
+            .iter()
+            .filter_map(|(power, until)| {
+                remaining(*until).map(|seconds| format!("{} {}s", power.name(), seconds))
+            })
+            .collect();
+            (*player, active.join("  "))
+        })
+        .collect();
+    for (_, (text, owner)) in &mut world.query::<(&mut components::Text, &components::PowerUps)>() {
+        let label = labels
+            .iter()
+            .find(|(player, _)| *player == owner.0)
+            .map(|(_, label)| label.as_str())
+            .filter(|label| !label.is_empty())
+            .unwrap_or("No power-ups");
+        if text.text() != label {
+            text.update_text(label.to_string());
+        }
+    }
+}
+
+/// A snake's head, where it is heading and the cell that takes it to, `None`
+/// when that is off the arena.
+type Head = (hecs::Entity, Vec2, Option<(i32, i32)>);
+
+/// Reports what every head is about to run into as `Collision` events, for
+/// `handle_collision_system` to turn into deaths and meals.
+pub fn head_collision_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        events,
+        arena,
+        grid,
+        ..
+    } = game_world;
+    let heads: Vec<Head> = world
+        .query::<(
+            &components::Snake,
+            &components::Position,
+            &components::Velocity,
+        )>()
+        .iter()
+        .map(|(ent, (_, pos, vel))| {
+            let cell = arena.step(pos.0, vel.0).map(arena::cell);
+            (ent, pos.0 + vel.0, cell)
+        })
+        .collect();
+
+    for (source_ent, next, source_cell) in heads.iter() {
+        let source_cell = match source_cell {
+            Some(cell) => *cell,
+            _ => {
+                // The edge of the arena is a wall without an entity of its own
+                events.collisions.send(Collision {
+                    target: *source_ent,
+                    source: *source_ent,
+                    pos: *next,
+                    kind: components::CollsionKind::Wall,
+                });
+                continue;
+            }
+        };
+        let pos = Vec2::new(source_cell.0 as f32, source_cell.1 as f32);
+        for (other_ent, _, other_cell) in heads.iter() {
+            if other_ent != source_ent && *other_cell == Some(source_cell) {
+                events.collisions.send(Collision {
+                    target: *other_ent,
+                    source: *source_ent,
+                    pos,
+                    kind: components::CollsionKind::Snake,
+                });
+            }
+        }
+        let occupants = grid.at(source_cell);
+        for (index, target) in occupants.iter().enumerate() {
+            // A snake can fill the same cell twice, one hit is enough
+            if occupants[..index].contains(target) {
+                continue;
+            }
+            let kind = match world.get::<components::Collision>(*target) {
+                Ok(col) => col.kind,
+                _ if world.get::<components::Snake>(*target).is_ok() => {
+                    components::CollsionKind::Snake
+                }
+                _ => continue,
+            };
+            events.collisions.send(Collision {
+                target: *target,
+                source: *source_ent,
+                pos,
+                kind,
+            });
+        }
+    }
+}
+
+pub fn handle_collision_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        events,
+        cursors,
+        arena,
+        ..
+    } = game_world;
+    let owner = |entity: hecs::Entity| -> Player {
+        *world
+            .get::<Player>(entity)
+            .expect("Snake heads and tails should have a components::Player")
+    };
+    let collisions: Vec<Collision> = events
+        .collisions
+        .read(&mut cursors.collisions)
+        .copied()
+        .collect();
+
+    // The cell a snake's head is about to move into, to tell hitting a head
+    // from hitting a tail
+    let heading_into = |entity: hecs::Entity| -> Option<(i32, i32)> {
+        let pos = world.get::<components::Position>(entity).ok()?;
+        let vel = world.get::<components::Velocity>(entity).ok()?;
+        arena.step(pos.0, vel.0).map(arena::cell)
+    };
+
+    // Deaths first, a snake that dies on this move doesn't get to eat as well
+    let mut dead: Vec<Player> = Vec::new();
+    // A shield takes every hit of the move it breaks on
+    let mut shielded: Vec<hecs::Entity> = Vec::new();
+    for collision in collisions.iter() {
+        let wall = match collision.kind {
+            components::CollsionKind::Snake => false,
+            components::CollsionKind::Wall => true,
+            _ => continue,
+        };
+        let source = collision.source;
+        let own_tail = !wall && collision.target == source;
+        if own_tail && world.get::<components::Ghost>(source).is_ok() {
+            continue;
+        }
+        if world.get::<components::Shield>(source).is_ok() {
+            if !shielded.contains(&source) {
+                shielded.push(source);
+            }
+            continue;
+        }
+        let player = owner(source);
+        dead.push(player);
+        let cause = if wall {
+            DeathCause::Wall
+        } else if !own_tail && heading_into(collision.target) == Some(arena::cell(collision.pos)) {
+            DeathCause::HeadOn {
+                other: owner(collision.target),
+            }
+        } else {
+            DeathCause::Tail {
+                owner: owner(collision.target),
+            }
+        };
+        events.deaths.send(SnakeDied { player, cause });
+    }
+    // Two heads can move onto the same food, the first one gets it
+    let mut claimed: Vec<hecs::Entity> = Vec::new();
+    for collision in collisions.iter() {
+        let player = match collision.kind {
+            components::CollsionKind::Snake | components::CollsionKind::Wall => continue,
+            _ => owner(collision.source),
+        };
+        if dead.contains(&player) || claimed.contains(&collision.target) {
+            continue;
+        }
+        claimed.push(collision.target);
+        let pos = world
+            .get::<components::Position>(collision.target)
+            .expect("Food and pickups should have components::Position");
+        if let Ok(food) = world.get::<components::Food>(collision.target) {
+            events.food_eaten.send(SnakeEatFood {
+                entity: collision.target,
+                pos: pos.0,
+                player,
+                kind: food.0,
+            });
+        }
+        if let Ok(pickup) = world.get::<components::Pickup>(collision.target) {
+            events.pickups.send(PickedUp {
+                entity: collision.target,
+                pos: pos.0,
+                player,
+                power: pickup.0,
+            });
+        }
+    }
+
+    for ent in shielded {
+        world
+            .remove_one::<components::Shield>(ent)
+            .expect("Entity was just queried");
+    }
+}
+
+/// Wins the game on the move that leaves every cell to the snakes and the
+/// walls, before the snakes run out of room to move.
+pub fn board_full_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        world,
+        arena,
+        grid,
+        events,
+        ..
+    } = game_world;
+    let filled = arena.cells().all(|pos| {
+        grid.at(arena::cell(pos)).iter().any(|ent| {
+            world.get::<components::Snake>(*ent).is_ok()
+                || world.get::<components::Obstacle>(*ent).is_ok()
+        })
+    });
+    if filled {
+        events.game_ended.send(GameEnded(Outcome::Win));
+    }
+}
+
+/// Ends the game on `GameOver` or `Win`, leaving the world as it was so the
+/// final frame can still be shown.
+pub fn game_over_system(game_world: &mut GameWorld) {
+    let GameWorld {
+        events,
+        cursors,
+        outcome,
+        ..
+    } = game_world;
+    let ended =
+        events
+            .game_ended
+            .read(&mut cursors.game_over)
+            .fold(None, |ended, GameEnded(event)| match event {
+                Outcome::Win => Some(Outcome::Win),
+                Outcome::GameOver => ended.or(Some(Outcome::GameOver)),
+            });
+    if ended.is_some() {
+        *outcome = ended;
+    }
+}
+
+/// Clears away a finished game and starts the next one.
+pub fn restart_system(game_world: &mut GameWorld) {
+    let GameWorld { world, grid, .. } = game_world;
+    // Keep the HUD around, everything else belongs to the finished game
+    let finished: Vec<hecs::Entity> = world
+        .query::<Option<&components::Text>>()
+        .iter()
+        .filter(|(_, text)| text.is_none())
+        .map(|(ent, _)| ent)
+        .collect();
+    for ent in finished {
+        world.despawn(ent).expect("Entity was just queried");
+    }
+    grid.clear();
+    let seed = match game_world.fixed_seed {
+        Some(seed) => seed,
+        _ => game_world.rng.next_u64(),
+    };
+    game_world.rng = Rng::new(seed);
+    game_world.players.iter_mut().for_each(PlayerState::reset);
+    game_world.ticks = 0;
+    game_world.outcome = None;
+    game_world.events.clear();
+    game_world.move_interval = game_world.rules.move_interval;
+    let step = game_world.clock.step();
+    game_world
+        .move_timer
+        .set_duration(game_world.move_interval, step);
+    game_world.move_timer.reset();
+    game_world.food_timer.reset();
+    update_score_text(&mut game_world.world, &game_world.players);
+    update_speed_text(&mut game_world.world, game_world.move_interval);
+    update_seed_text_system(game_world);
+    create_level_system(game_world);
+    create_snake_system(game_world);
+}
+
+pub fn update_seed_text_system(game_world: &mut GameWorld) {
+    let GameWorld { world, rng, .. } = game_world;
+    for (_, (text, _seed)) in &mut world.query::<(&mut components::Text, &components::Seed)>() {
+        text.update_text(format!("Seed:  {}", rng.seed()));
+    }
+}
+
+/// Runs a single fixed simulation tick through the schedule. Returns true
+/// when the game ended on it, after which the world stays frozen until
+/// `restart_system`.
+pub fn tick_systems(game_world: &mut GameWorld) -> bool {
+    if game_world.outcome.is_some() {
+        return false;
+    }
+    game_world.ticks += 1;
+    game_world.events.update();
+    game_world.move_timer.tick();
+    game_world.food_timer.tick();
+    let moving = game_world.move_timer.finished();
+    let spawning = game_world.food_timer.finished();
+
+    let systems = game_world
+        .schedule
+        .systems()
+        .expect("The GameWorld schedule has conflicting constraints");
+    for system in systems {
+        let due = match system.on {
+            RunOn::EveryTick => true,
+            RunOn::Move => moving,
+            RunOn::FoodSpawn => spawning,
+        };
+        if due {
+            (system.run)(game_world);
+        }
+    }
+
+    if moving {
+        game_world.move_timer.reset();
+    }
+    if spawning {
+        game_world.food_timer.reset();
+    }
+    game_world.last_input = game_world.input;
+    game_world.input.reset();
+    game_world.outcome.is_some()
+}
@@ -0,0 +1,100 @@
+//! Versioned text files.
+//!
+//! Rules, levels, replays and high scores are all kept as plain text: a
+//! `magic version` header line, then one `key value` entry per line. Each
+//! format knows its own keys, what they share lives here: the error type,
+//! the header and reading and writing the files.
+use core::str::FromStr;
+use std::fmt::Write;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum FileError {
+    Io(std::io::Error),
+    MissingHeader,
+    UnsupportedVersion(u32),
+    /// The line with this number, counting from one, didn't parse.
+    BadLine(usize),
+}
+
+impl From<std::io::Error> for FileError {
+    fn from(err: std::io::Error) -> Self {
+        FileError::Io(err)
+    }
+}
+
+pub fn write_header(out: &mut String, magic: &str, version: u32) {
+    let _ = writeln!(out, "{} {}", magic, version);
+}
+
+/// Checks that `contents` starts with the header for `magic` at `version`
+/// and hands out the lines after it, trimmed and numbered from one.
+pub fn lines<'a>(
+    contents: &'a str,
+    magic: &str,
+    version: u32,
+) -> Result<impl Iterator<Item = (usize, &'a str)> + 'a, FileError> {
+    let mut lines = contents
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .map(|(index, line)| (index + 1, line));
+
+    let found = match lines.next().and_then(|(_, line)| line.split_once(' ')) {
+        Some((found, found_version)) if found == magic => {
+            u32::from_str(found_version).map_err(|_| FileError::MissingHeader)?
+        }
+        _ => return Err(FileError::MissingHeader),
+    };
+    if found != version {
+        return Err(FileError::UnsupportedVersion(found));
+    }
+    Ok(lines)
+}
+
+/// Writes `contents` to `path`, creating its directory first.
+pub fn save<P: AsRef<Path>>(path: P, contents: &str) -> Result<(), FileError> {
+    if let Some(dir) = path.as_ref().parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<String, FileError> {
+    Ok(std::fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_after_the_header() {
+        let mut out = String::new();
+        write_header(&mut out, "snake-test", 2);
+        out.push_str("a 1\n\n  b 2  \n");
+        let lines: Vec<_> = lines(&out, "snake-test", 2).unwrap().collect();
+        assert_eq!(lines, [(2, "a 1"), (3, ""), (4, "b 2")]);
+    }
+
+    #[test]
+    fn header_is_checked() {
+        assert!(matches!(
+            lines("", "snake-test", 1).err(),
+            Some(FileError::MissingHeader)
+        ));
+        assert!(matches!(
+            lines("snake-other 1", "snake-test", 1).err(),
+            Some(FileError::MissingHeader)
+        ));
+        assert!(matches!(
+            lines("snake-test x", "snake-test", 1).err(),
+            Some(FileError::MissingHeader)
+        ));
+        assert!(matches!(
+            lines("snake-test 3", "snake-test", 1).err(),
+            Some(FileError::UnsupportedVersion(3))
+        ));
+    }
+}
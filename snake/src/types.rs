@@ -2,6 +2,7 @@ use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
 // TODO(jhurstwright): Create an alignment
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct Rect {
     positon: glam::Vec2,
@@ -71,10 +72,10 @@ impl Color {
     }
     pub fn as_u8(&self) -> [u8; 4] {
         [
-            (self.0[0] * 255.0f32).max(0.0).min(255.0) as u8,
-            (self.0[1] * 255.0f32).max(0.0).min(255.0) as u8,
-            (self.0[2] * 255.0f32).max(0.0).min(255.0) as u8,
-            (self.0[3] * 255.0f32).max(0.0).min(255.0) as u8,
+            (self.0[0] * 255.0f32).clamp(0.0, 255.0) as u8,
+            (self.0[1] * 255.0f32).clamp(0.0, 255.0) as u8,
+            (self.0[2] * 255.0f32).clamp(0.0, 255.0) as u8,
+            (self.0[3] * 255.0f32).clamp(0.0, 255.0) as u8,
         ]
     }
 }
@@ -90,24 +91,24 @@ impl From<(u8, u8, u8, u8)> for Color {
     }
 }
 
-impl Into<[u8; 4]> for Color {
-    fn into(self) -> [u8; 4] {
-        self.as_u8()
+impl From<Color> for [u8; 4] {
+    fn from(color: Color) -> Self {
+        color.as_u8()
     }
 }
 
-impl Into<(u8, u8, u8, u8)> for Color {
-    fn into(self) -> (u8, u8, u8, u8) {
-        let x = (self.0[0] * 255.0f32).max(0.0).min(255.0) as u8;
-        let y = (self.0[1] * 255.0f32).max(0.0).min(255.0) as u8;
-        let z = (self.0[2] * 255.0f32).max(0.0).min(255.0) as u8;
-        let w = (self.0[3] * 255.0f32).max(0.0).min(255.0) as u8;
+impl From<Color> for (u8, u8, u8, u8) {
+    fn from(color: Color) -> Self {
+        let x = (color.0[0] * 255.0f32).clamp(0.0, 255.0) as u8;
+        let y = (color.0[1] * 255.0f32).clamp(0.0, 255.0) as u8;
+        let z = (color.0[2] * 255.0f32).clamp(0.0, 255.0) as u8;
+        let w = (color.0[3] * 255.0f32).clamp(0.0, 255.0) as u8;
         (x, y, z, w)
     }
 }
 
-impl Into<(f32, f32, f32, f32)> for Color {
-    fn into(self) -> (f32, f32, f32, f32) {
-        (self.0[0], self.0[1], self.0[2], self.0[3])
+impl From<Color> for (f32, f32, f32, f32) {
+    fn from(color: Color) -> Self {
+        (color.0[0], color.0[1], color.0[2], color.0[3])
     }
 }
\ No newline at end of file
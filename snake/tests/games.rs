@@ -0,0 +1,418 @@
+//! Whole games played headless by the AI controllers.
+use glam::Vec2;
+use snake::ai;
+use snake::arena::{Arena, EdgeRule};
+use snake::clock::{self, GameClock, DEFAULT_STEP};
+use snake::components::{self, Camera2D, Direction, Input, Player};
+use snake::events::DeathCause;
+use snake::food::FoodKind;
+use snake::level::Level;
+use snake::replay::{Playback, Recorder, Replay};
+use snake::rules::RuleSet;
+use snake::systems::{self, GameWorld, Outcome};
+
+/// Longest game a test lets run before giving up on it.
+const MAX_TICKS: u64 = 200_000;
+
+fn game(seed: u64, rules: RuleSet, level: Level, controllers: &[&str]) -> GameWorld {
+    let (clock, _) = GameClock::manual(DEFAULT_STEP);
+    let mut game_world = GameWorld::new(Camera2D::new(&Input::default(), 20.), clock, seed);
+    game_world.set_rules(rules);
+    game_world.set_player_count(controllers.len());
+    for (state, name) in game_world.players.iter_mut().zip(controllers) {
+        state.controller = ai::controller(name);
+    }
+    game_world.level = level;
+    systems::create_level_system(&mut game_world);
+    systems::create_snake_system(&mut game_world);
+    game_world
+}
+
+/// Runs the ticks up to and including the next `count` moves.
+fn moves(game_world: &mut GameWorld, count: u32) {
+    let ticks = count * game_world.move_timer.period();
+    for _ in 0..ticks {
+        systems::tick_systems(game_world);
+    }
+}
+
+fn snake(game_world: &GameWorld, player: Player) -> hecs::Entity {
+    game_world
+        .world
+        .query::<(&Player, &components::Snake)>()
+        .iter()
+        .find(|(_, (owner, _))| **owner == player)
+        .map(|(ent, _)| ent)
+        .expect("The player should have a snake")
+}
+
+/// Starts a status on the player's snake, e.g. a power-up.
+fn give<T: hecs::Component>(game_world: &mut GameWorld, player: Player, status: T) {
+    let snake = snake(game_world, player);
+    game_world
+        .world
+        .insert_one(snake, status)
+        .expect("The snake was just found");
+}
+
+fn shield(game_world: &mut GameWorld, player: Player) {
+    give(game_world, player, components::Shield { tick: u64::MAX });
+}
+
+/// Puts a food of `kind` on `cell`, the way `add_food_system` would.
+fn food(game_world: &mut GameWorld, kind: FoodKind, cell: (i32, i32)) -> hecs::Entity {
+    let pos = components::Position(Vec2::new(cell.0 as f32, cell.1 as f32));
+    let food = game_world
+        .world
+        .spawn((pos, components::Collision::food(), components::Food(kind)));
+    if let Some(lifetime) = kind.spec().lifetime {
+        let tick = game_world.ticks + clock::ticks_for(lifetime, game_world.clock.step()) as u64;
+        game_world
+            .world
+            .insert_one(food, components::Expires { tick })
+            .expect("Food was just spawned");
+    }
+    game_world.grid.insert(food, cell);
+    food
+}
+
+fn cell_of(game_world: &GameWorld, entity: hecs::Entity) -> (f32, f32) {
+    let pos = game_world
+        .world
+        .get::<components::Position>(entity)
+        .expect("The entity should have a position");
+    (pos.0.x, pos.0.y)
+}
+
+fn head(game_world: &GameWorld, player: Player) -> Option<(f32, f32)> {
+    game_world
+        .world
+        .query::<(&Player, &components::Snake, &components::Position)>()
+        .iter()
+        .find(|(_, (owner, _, _))| **owner == player)
+        .map(|(_, (_, _, pos))| (pos.0.x, pos.0.y))
+}
+
+fn play(game_world: &mut GameWorld) -> Outcome {
+    for _ in 0..MAX_TICKS {
+        if systems::tick_systems(game_world) {
+            break;
+        }
+    }
+    game_world.outcome.expect("The game should have ended")
+}
+
+#[test]
+fn filling_the_board_wins() {
+    let rules = RuleSet {
+        arena: Arena::new(6, 6, EdgeRule::Walls),
+        ..RuleSet::default()
+    };
+    let mut game_world = game(7, rules, Level::new(), &["hamiltonian"]);
+    assert_eq!(play(&mut game_world), Outcome::Win);
+    let results = game_world.results();
+    assert_eq!(results[0].length, 36);
+    assert_eq!(results[0].cause, None);
+}
+
+#[test]
+fn shield_takes_the_edge() {
+    let rules = RuleSet {
+        arena: Arena::new(6, 6, EdgeRule::Walls),
+        ..RuleSet::default()
+    };
+    let mut game_world = game(7, rules, Level::new(), &[""]);
+    shield(&mut game_world, Player(0));
+    // Up from the middle row, the third move would leave the arena
+    moves(&mut game_world, 3);
+    assert_eq!(game_world.outcome, None);
+    assert_eq!(head(&game_world, Player(0)), Some((0., 2.)));
+
+    // The shield is spent, the next move into the edge kills
+    moves(&mut game_world, 1);
+    assert_eq!(game_world.outcome, Some(Outcome::GameOver));
+    assert_eq!(game_world.results()[0].cause, Some(DeathCause::Wall));
+}
+
+#[test]
+fn shield_takes_a_head_on() {
+    let rules = RuleSet {
+        arena: Arena::new(6, 6, EdgeRule::Walls),
+        ..RuleSet::default()
+    };
+    let mut game_world = game(7, rules, Level::new(), &["", ""]);
+    shield(&mut game_world, Player(1));
+    // The snakes start a column apart and turn into the one between them
+    game_world.input.press_for(Player(0), Direction::Right);
+    game_world.input.press_for(Player(1), Direction::Left);
+    moves(&mut game_world, 1);
+    assert_eq!(game_world.outcome, Some(Outcome::GameOver));
+    let results = game_world.results();
+    assert_eq!(
+        results[0].cause,
+        Some(DeathCause::HeadOn { other: Player(1) })
+    );
+    assert_eq!(results[1].cause, None);
+}
+
+#[test]
+fn food_goes_to_one_shielded_snake() {
+    let rules = RuleSet {
+        arena: Arena::new(6, 6, EdgeRule::Walls),
+        ..RuleSet::default()
+    };
+    let mut game_world = game(7, rules, Level::new(), &["", ""]);
+    shield(&mut game_world, Player(0));
+    shield(&mut game_world, Player(1));
+    let plain = food(&mut game_world, FoodKind::Plain, (0, 0));
+    game_world.input.press_for(Player(0), Direction::Right);
+    game_world.input.press_for(Player(1), Direction::Left);
+    moves(&mut game_world, 1);
+    assert_eq!(game_world.outcome, None);
+    assert!(!game_world.world.contains(plain));
+    let eaten: i32 = game_world.players.iter().map(|state| state.score).sum();
+    assert_eq!(eaten, 1);
+}
+
+/// Turns the snake back on itself, the third move runs into its own tail.
+fn curl(game_world: &mut GameWorld) {
+    for turn in [Direction::Right, Direction::Down, Direction::Left].iter() {
+        game_world.input.press_for(Player(0), *turn);
+        moves(game_world, 1);
+    }
+}
+
+#[test]
+fn ghost_passes_through_its_own_tail() {
+    let rules = RuleSet {
+        arena: Arena::new(10, 10, EdgeRule::Walls),
+        start_length: 6,
+        ..RuleSet::default()
+    };
+    let mut game_world = game(7, rules, Level::new(), &[""]);
+    curl(&mut game_world);
+    assert_eq!(game_world.outcome, Some(Outcome::GameOver));
+    assert_eq!(
+        game_world.results()[0].cause,
+        Some(DeathCause::Tail { owner: Player(0) })
+    );
+
+    let mut game_world = game(7, rules, Level::new(), &[""]);
+    give(
+        &mut game_world,
+        Player(0),
+        components::Ghost { tick: u64::MAX },
+    );
+    curl(&mut game_world);
+    assert_eq!(game_world.outcome, None);
+    assert_eq!(head(&game_world, Player(0)), Some((0., -1.)));
+}
+
+#[test]
+fn magnet_pulls_food_a_cell_a_move() {
+    let rules = RuleSet {
+        arena: Arena::new(10, 10, EdgeRule::Walls),
+        ..RuleSet::default()
+    };
+    let mut game_world = game(7, rules, Level::new(), &[""]);
+    give(
+        &mut game_world,
+        Player(0),
+        components::Magnet { tick: u64::MAX },
+    );
+    let plain = food(&mut game_world, FoodKind::Plain, (4, -2));
+    // Along whichever axis the head is further off on
+    moves(&mut game_world, 1);
+    assert_eq!(cell_of(&game_world, plain), (3., -2.));
+    moves(&mut game_world, 1);
+    assert_eq!(cell_of(&game_world, plain), (3., -1.));
+}
+
+#[test]
+fn slow_mo_lengthens_the_moves() {
+    let mut game_world = game(7, RuleSet::default(), Level::new(), &[""]);
+    let period = game_world.move_timer.period();
+    give(&mut game_world, Player(0), components::SlowMo { tick: 3 });
+    systems::tick_systems(&mut game_world);
+    assert!(game_world.move_timer.period() > period);
+
+    // Back to normal once it runs out
+    systems::tick_systems(&mut game_world);
+    systems::tick_systems(&mut game_world);
+    assert_eq!(game_world.move_timer.period(), period);
+}
+
+#[test]
+fn statuses_expire_at_their_tick() {
+    let mut game_world = game(7, RuleSet::default(), Level::new(), &[""]);
+    let snake = snake(&game_world, Player(0));
+    let tick = game_world.ticks + 5;
+    give(&mut game_world, Player(0), components::Ghost { tick });
+    give(&mut game_world, Player(0), components::SlowMo { tick });
+    give(&mut game_world, Player(0), components::Magnet { tick });
+    give(&mut game_world, Player(0), components::Shield { tick });
+    let running = |game_world: &GameWorld| {
+        [
+            game_world.world.get::<components::Ghost>(snake).is_ok(),
+            game_world.world.get::<components::SlowMo>(snake).is_ok(),
+            game_world.world.get::<components::Magnet>(snake).is_ok(),
+            game_world.world.get::<components::Shield>(snake).is_ok(),
+        ]
+    };
+    while game_world.ticks + 1 < tick {
+        systems::tick_systems(&mut game_world);
+    }
+    assert_eq!(running(&game_world), [true; 4]);
+    systems::tick_systems(&mut game_world);
+    assert_eq!(game_world.ticks, tick);
+    assert_eq!(running(&game_world), [false; 4]);
+}
+
+#[test]
+fn poison_shrinks_the_tail() {
+    let rules = RuleSet {
+        arena: Arena::new(10, 10, EdgeRule::Walls),
+        start_length: 6,
+        ..RuleSet::default()
+    };
+    let mut game_world = game(7, rules, Level::new(), &[""]);
+    let snake = snake(&game_world, Player(0));
+    food(&mut game_world, FoodKind::Poison, (0, 1));
+    moves(&mut game_world, 1);
+    let length = game_world
+        .world
+        .get::<components::Body>(snake)
+        .expect("The snake should have a body")
+        .len();
+    assert_eq!(length, 3);
+    // The two cells at the tip of the tail are free again
+    for (cell, filled) in [((0, -2), true), ((0, -3), false), ((0, -4), false)].iter() {
+        assert_eq!(game_world.grid.at(*cell).contains(&snake), *filled);
+    }
+}
+
+#[test]
+fn golden_food_expires() {
+    let rules = RuleSet {
+        arena: Arena::new(10, 10, EdgeRule::Wrap),
+        ..RuleSet::default()
+    };
+    let mut game_world = game(7, rules, Level::new(), &[""]);
+    let golden = food(&mut game_world, FoodKind::Golden, (4, -4));
+    let tick = game_world
+        .world
+        .get::<components::Expires>(golden)
+        .expect("Golden food should expire")
+        .tick;
+    while game_world.ticks + 1 < tick {
+        systems::tick_systems(&mut game_world);
+    }
+    assert!(game_world.world.contains(golden));
+    systems::tick_systems(&mut game_world);
+    assert!(!game_world.world.contains(golden));
+    assert!(!game_world.grid.at((4, -4)).contains(&golden));
+}
+
+#[test]
+fn fast_food_paces_the_moves_for_a_while() {
+    let rules = RuleSet {
+        arena: Arena::new(10, 10, EdgeRule::Wrap),
+        ..RuleSet::default()
+    };
+    let mut game_world = game(7, rules, Level::new(), &[""]);
+    let snake = snake(&game_world, Player(0));
+    let period = game_world.move_timer.period();
+    food(&mut game_world, FoodKind::Fast, (0, 1));
+    moves(&mut game_world, 1);
+    let pace = *game_world
+        .world
+        .get::<components::Pace>(snake)
+        .expect("Fast food should pace the snake");
+    assert_eq!(pace.scale, 0.6);
+    assert_eq!(
+        pace.tick,
+        game_world.ticks + clock::ticks_for(5., game_world.clock.step()) as u64
+    );
+    let paced = game_world.move_timer.period();
+    assert!(paced < period);
+
+    while game_world.ticks < pace.tick {
+        systems::tick_systems(&mut game_world);
+    }
+    assert!(game_world.world.get::<components::Pace>(snake).is_err());
+    assert!(game_world.move_timer.period() > paced);
+}
+
+#[test]
+fn head_on_kills_both() {
+    let rules = RuleSet {
+        arena: Arena::new(6, 6, EdgeRule::Walls),
+        ..RuleSet::default()
+    };
+    let mut game_world = game(7, rules, Level::new(), &["", ""]);
+    game_world.input.press_for(Player(0), Direction::Right);
+    game_world.input.press_for(Player(1), Direction::Left);
+    moves(&mut game_world, 1);
+    let results = game_world.results();
+    assert_eq!(
+        results[0].cause,
+        Some(DeathCause::HeadOn { other: Player(1) })
+    );
+    assert_eq!(
+        results[1].cause,
+        Some(DeathCause::HeadOn { other: Player(0) })
+    );
+}
+
+#[test]
+fn snakes_spawn_clear_of_walls() {
+    let mut level = Level::new();
+    level.add_wall((0, 0));
+    level.add_wall((0, 2));
+    level.add_line((1, -3), (1, 3));
+    let mut game_world = game(7, RuleSet::default(), level, &[""]);
+    assert_eq!(head(&game_world, Player(0)), Some((-1., 0.)));
+    moves(&mut game_world, 5);
+    assert_eq!(game_world.outcome, None);
+}
+
+#[test]
+fn replays_reproduce_the_game() {
+    let rules = RuleSet {
+        arena: Arena::new(16, 12, EdgeRule::Walls),
+        ..RuleSet::default()
+    };
+    let mut level = Level::new();
+    level.add_line((-4, 3), (4, 3));
+    let mut game_world = game(99, rules, level.clone(), &["seeker", "survivor"]);
+    let mut recorder = Recorder::new(99, rules, level, 2);
+    for _ in 0..MAX_TICKS {
+        let ended = systems::tick_systems(&mut game_world);
+        recorder.record(&game_world.last_input);
+        if ended {
+            break;
+        }
+    }
+    let outcome = game_world.outcome.expect("The game should have ended");
+    let replay = recorder.finish();
+    assert!(!replay.turns.is_empty());
+
+    // Played back from the saved text, the way the game loads them
+    let mut playback = Playback::new(Replay::parse(&replay.serialize()).unwrap());
+    let mut played = game(
+        playback.seed(),
+        playback.rules(),
+        playback.level().clone(),
+        &["", ""],
+    );
+    while let Some(input) = playback.next_input() {
+        played.input = input;
+        if systems::tick_systems(&mut played) {
+            break;
+        }
+    }
+    assert!(playback.finished());
+    assert_eq!(played.outcome, Some(outcome));
+    assert_eq!(played.ticks, game_world.ticks);
+    assert_eq!(played.results(), game_world.results());
+}